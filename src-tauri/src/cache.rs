@@ -0,0 +1,510 @@
+use std::{
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex, PoisonError,
+  },
+};
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use kuchikiki::{parse_html, traits::TendrilSink};
+use log::{debug, info, warn};
+use reqwest::{
+  header::{HeaderMap, HeaderValue, REFERER},
+  Url,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use tauri::{async_runtime::spawn_blocking, AppHandle};
+
+use crate::{
+  app_handle::get_app_handle,
+  db::DbAccess,
+  download::Downloader,
+  events::{EmitEvent, MediaCachedEvent},
+  job::get_cache_quota,
+};
+
+/// 单张图片的大小上限，字节
+pub const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
+/// 每次后台缓存的图片数量上限
+const MAX_PREFETCH: usize = 100;
+/// 积累的访问记录达到此数量时立即写入数据库
+const MAX_PENDING_ACCESSES: usize = 256;
+/// 图片下载失败后第一次重试的等待时间，秒。之后每失败一次加倍
+const RETRY_DELAY: i64 = 60 * 60;
+/// 图片下载失败后重试的最长等待时间，秒
+const MAX_RETRY_DELAY: i64 = 7 * 24 * 60 * 60;
+/// 还在等待重试的失败记录，`?1` 为当前时间
+const RETRY_PENDING: &str =
+  "content_type IS NULL AND failed_at + MIN(?2 << MIN(failures - 1, 20), ?3) > ?1";
+
+/// 是否正在后台缓存图片
+static PREFETCHING: AtomicBool = AtomicBool::new(false);
+/// 正在按需下载的图片
+static DOWNLOADING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// 还没有写入数据库的访问记录，哈希及访问时间。读取缓存不占用写连接，访问时间批量更新
+static PENDING_ACCESSES: Mutex<Vec<(String, i64)>> = Mutex::new(Vec::new());
+
+/// 缓存的图片
+pub struct CachedImage {
+  /// MIME 类型
+  pub content_type: String,
+  /// 图片数据
+  pub data: Vec<u8>,
+}
+
+/// 图片缓存目录
+fn cache_dir(app_handle: &AppHandle) -> Result<PathBuf> {
+  let dir = app_handle
+    .path_resolver()
+    .app_cache_dir()
+    .ok_or_else(|| anyhow!("The app cache directory is unavailable."))?
+    .join("images");
+  fs::create_dir_all(&dir)?;
+  Ok(dir)
+}
+
+/// URL 的哈希，作为缓存的键和文件名，相同 URL 的图片只缓存一份
+pub fn url_hash(url: &str) -> String {
+  Sha256::digest(url.as_bytes())
+    .iter()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+/// 查找 HTML 中的图片，相对 URL 以 `base` 为基准，只返回 HTTP(S) 的图片
+pub fn find_images(html: &str, base: Option<&Url>) -> Vec<String> {
+  let document = parse_html().one(html);
+  let Ok(images) = document.select("img") else {
+    return Vec::new();
+  };
+  let mut urls = Vec::new();
+
+  for image in images {
+    let attributes = image.attributes.borrow();
+    let Some(src) = attributes.get("src") else {
+      continue;
+    };
+    let url = match base {
+      Some(base) => base.join(src.trim()),
+      None => Url::parse(src.trim()),
+    };
+
+    if let Ok(url) = url {
+      if matches!(url.scheme(), "http" | "https") && !urls.contains(&url.to_string()) {
+        urls.push(url.to_string());
+      }
+    }
+  }
+
+  urls
+}
+
+/// 记录文章引用的图片，供后台缓存和清理使用
+pub fn add_article_images(
+  db: &Connection,
+  article_id: i64,
+  link: Option<&str>,
+  htmls: &[Option<&str>],
+) -> Result<()> {
+  let base = link.and_then(|link| Url::parse(link).ok());
+  let mut stmt =
+    db.prepare("INSERT OR IGNORE INTO article_images (article_id, hash, url) VALUES (?1, ?2, ?3)")?;
+
+  for html in htmls.iter().flatten() {
+    for url in find_images(html, base.as_ref()) {
+      stmt.execute(params![article_id, url_hash(&url), url])?;
+    }
+  }
+
+  Ok(())
+}
+
+/// 删除缓存的图片
+fn remove(db: &Connection, dir: &Path, hash: &str) -> Result<()> {
+  let path = dir.join(hash);
+
+  if path.exists() {
+    fs::remove_file(path)?;
+  }
+
+  db.execute("DELETE FROM image_cache WHERE hash = ?1", [hash])?;
+  Ok(())
+}
+
+/// 从缓存中读取图片，并记录访问时间。没有缓存或上次下载失败时返回 `None`。
+///
+/// 同步执行，供自定义协议使用，在异步代码中需要放到阻塞线程上
+pub fn get_cached(app_handle: &AppHandle, url: &str) -> Result<Option<CachedImage>> {
+  let dir = cache_dir(app_handle)?;
+  let hash = url_hash(url);
+  let content_type: Option<Option<String>> = app_handle.db_sync(|db| {
+    db.query_row(
+      "SELECT content_type FROM image_cache WHERE hash = ?1",
+      [&hash],
+      |row| row.get(0),
+    )
+    .optional()
+  })?;
+  let Some(Some(content_type)) = content_type else {
+    return Ok(None);
+  };
+
+  let data = match fs::read(dir.join(&hash)) {
+    Ok(data) => data,
+    Err(err) => {
+      // 文件被删除了，重新下载
+      debug!("Failed to read cached image {}: {:?}", url, err);
+      app_handle.db_mut_sync(|db| remove(db, &dir, &hash))?;
+      return Ok(None);
+    }
+  };
+
+  let full = {
+    let mut accesses = PENDING_ACCESSES
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner);
+    accesses.push((hash, Local::now().timestamp()));
+    accesses.len() >= MAX_PENDING_ACCESSES
+  };
+
+  if full {
+    app_handle.db_mut_sync(flush_accesses)?;
+  }
+
+  Ok(Some(CachedImage { content_type, data }))
+}
+
+/// 将积累的访问时间写入数据库
+fn flush_accesses(db: &mut Connection) -> Result<()> {
+  let accesses = std::mem::take(
+    &mut *PENDING_ACCESSES
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner),
+  );
+
+  if accesses.is_empty() {
+    return Ok(());
+  }
+
+  let tx = db.transaction()?;
+
+  {
+    let mut stmt = tx.prepare("UPDATE image_cache SET accessed_at = ?2 WHERE hash = ?1")?;
+
+    for (hash, accessed_at) in &accesses {
+      stmt.execute(params![hash, accessed_at])?;
+    }
+  }
+
+  tx.commit()?;
+  Ok(())
+}
+
+/// 缓存图片，缓存超出 `quota` 字节时按最近访问时间淘汰
+pub async fn store(
+  app_handle: &AppHandle,
+  url: &str,
+  content_type: &str,
+  data: Vec<u8>,
+  quota: u64,
+) -> Result<()> {
+  let dir = cache_dir(app_handle)?;
+  let hash = url_hash(url);
+  let content_type = content_type.to_string();
+  let size = data.len();
+
+  // 先写入文件，不占用写连接
+  let path = dir.join(&hash);
+  spawn_blocking(move || fs::write(path, data)).await??;
+
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      db.execute(
+        "REPLACE INTO image_cache (hash, content_type, size, accessed_at) VALUES (?1, ?2, ?3, ?4)",
+        params![hash, content_type, size, Local::now().timestamp()],
+      )?;
+      // 淘汰前写入访问时间，刚访问过的图片不会被淘汰
+      flush_accesses(db)?;
+      evict(db, &dir, quota)
+    })
+    .await
+}
+
+/// 记录下载失败的图片及失败次数，等待一段时间后才重试，失败越多等待越久
+async fn store_failure(app_handle: &AppHandle, url: &str) -> Result<()> {
+  let hash = url_hash(url);
+
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      db.execute(
+        "INSERT INTO image_cache (hash, content_type, size, accessed_at, failures, failed_at) VALUES (?1, NULL, 0, ?2, 1, ?2)
+          ON CONFLICT (hash) DO UPDATE SET failures = failures + 1, failed_at = excluded.failed_at WHERE content_type IS NULL",
+        params![hash, Local::now().timestamp()],
+      )?;
+      Ok(())
+    })
+    .await
+}
+
+/// 淘汰最久没有访问的图片，直到缓存不超过 `quota` 字节
+fn evict(db: &Connection, dir: &Path, quota: u64) -> Result<()> {
+  let total: u64 = db.query_row("SELECT IFNULL(SUM(size), 0) FROM image_cache", [], |row| {
+    row.get(0)
+  })?;
+
+  if total <= quota {
+    return Ok(());
+  }
+
+  let mut stmt =
+    db.prepare("SELECT hash, size FROM image_cache WHERE size > 0 ORDER BY accessed_at")?;
+  let images = stmt
+    .query_map([], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  let mut total = total;
+
+  for (hash, size) in images {
+    if total <= quota {
+      break;
+    }
+
+    remove(db, dir, &hash)?;
+    total -= size;
+  }
+
+  debug!("Image cache evicted to {} bytes", total);
+  Ok(())
+}
+
+/// 定期维护图片缓存：写入访问时间，删除不再被引用的图片，并按缓存大小上限淘汰
+pub async fn maintain() {
+  let Some(app_handle) = get_app_handle() else {
+    return;
+  };
+
+  let result = match cache_dir(&app_handle) {
+    Ok(dir) => {
+      app_handle
+        .db_mut(move |db| -> Result<()> {
+          flush_accesses(db)?;
+          let quota = get_cache_quota(db)?;
+          evict(db, &dir, quota)
+        })
+        .await
+    }
+    Err(err) => Err(err),
+  };
+
+  if let Err(err) = result {
+    warn!("Failed to maintain the image cache: {:?}", err);
+  }
+}
+
+/// 获取文章引用的图片，清理文章前调用
+pub fn get_article_images(db: &Connection, article_ids: &[i64]) -> Result<Vec<String>> {
+  let mut stmt = db.prepare("SELECT DISTINCT hash FROM article_images WHERE article_id = ?1")?;
+  let mut hashes = HashSet::new();
+
+  for id in article_ids {
+    let rows = stmt.query_map([id], |row| row.get::<_, String>(0))?;
+
+    for hash in rows {
+      hashes.insert(hash?);
+    }
+  }
+
+  Ok(hashes.into_iter().collect())
+}
+
+/// 删除已清理文章的图片中不再被其他文章引用的部分。
+///
+/// 其他图片可能是界面直接请求而缓存的，不在 `article_images` 中，只按缓存大小淘汰。
+pub fn cleanup(app_handle: &AppHandle, db: &Connection, hashes: &[String]) -> Result<()> {
+  let dir = cache_dir(app_handle)?;
+  let mut stmt = db.prepare("SELECT COUNT(*) FROM article_images WHERE hash = ?1")?;
+  let mut removed = 0;
+
+  for hash in hashes {
+    let count: i64 = stmt.query_row([hash], |row| row.get(0))?;
+
+    if count == 0 {
+      remove(db, &dir, hash)?;
+      removed += 1;
+    }
+  }
+
+  if removed > 0 {
+    info!("Removed {} cached images", removed);
+  }
+
+  Ok(())
+}
+
+/// 获取未读文章中还没有缓存的图片及文章链接，新文章优先。下载失败的图片到了重试时间才返回
+fn get_uncached(db: &Connection) -> Result<Vec<(String, Option<String>)>> {
+  let mut stmt = db.prepare(&format!(
+    "SELECT article_images.url, articles.link FROM article_images JOIN articles ON articles.id = article_images.article_id WHERE articles.unread = 1 AND article_images.hash NOT IN (SELECT hash FROM image_cache WHERE content_type IS NOT NULL OR ({})) ORDER BY articles.id DESC LIMIT ?4",
+    RETRY_PENDING
+  ))?;
+  let images = stmt
+    .query_map(
+      params![
+        Local::now().timestamp(),
+        RETRY_DELAY,
+        MAX_RETRY_DELAY,
+        MAX_PREFETCH
+      ],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(images)
+}
+
+/// 后台缓存期间持有，释放时清除标志，任务出错或崩溃后仍可以再次开始
+struct PrefetchGuard;
+
+impl PrefetchGuard {
+  /// 开始后台缓存，已经在缓存时返回 `None`
+  fn acquire() -> Option<Self> {
+    PREFETCHING
+      .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+      .ok()
+      .map(|_| Self)
+  }
+}
+
+impl Drop for PrefetchGuard {
+  fn drop(&mut self) {
+    PREFETCHING.store(false, Ordering::Release);
+  }
+}
+
+/// 在后台缓存未读文章中的图片，上一次还没有结束时直接返回
+pub fn prefetch(app_handle: AppHandle) {
+  let Some(guard) = PrefetchGuard::acquire() else {
+    return;
+  };
+
+  tauri::async_runtime::spawn(async move {
+    let _guard = guard;
+
+    if let Err(err) = prefetch_images(&app_handle).await {
+      warn!("Failed to cache images: {:?}", err);
+    }
+  });
+}
+
+/// 依次下载并缓存未读文章中的图片，失败的图片等待一段时间后重试
+async fn prefetch_images(app_handle: &AppHandle) -> Result<()> {
+  let downloader = Downloader::new(app_handle).await?;
+  let images = app_handle.db(get_uncached).await?;
+  let mut seen = HashSet::new();
+
+  for (url, link) in images {
+    if !seen.insert(url.clone()) {
+      continue;
+    }
+
+    if let Err(err) = cache_image(app_handle, &downloader, &url, link.as_deref()).await {
+      warn!("Failed to store image {}: {:?}", url, err);
+    }
+  }
+
+  Ok(())
+}
+
+/// 下载并缓存一张图片，失败时记录下来。返回是否缓存成功
+async fn cache_image(
+  app_handle: &AppHandle,
+  downloader: &Downloader,
+  url: &str,
+  referer: Option<&str>,
+) -> Result<bool> {
+  let mut headers = HeaderMap::new();
+
+  if let Some(referer) = referer.and_then(|referer| HeaderValue::from_str(referer).ok()) {
+    headers.insert(REFERER, referer);
+  }
+
+  let result = match Url::parse(url) {
+    Ok(parsed) => downloader.get_image(&parsed, headers, MAX_IMAGE_SIZE).await,
+    Err(err) => Err(err.into()),
+  };
+
+  match result {
+    Ok((content_type, data)) => {
+      store(app_handle, url, &content_type, data, downloader.cache_quota).await?;
+      Ok(true)
+    }
+    Err(err) => {
+      debug!("Failed to cache image {}: {:?}", url, err);
+      store_failure(app_handle, url).await?;
+      Ok(false)
+    }
+  }
+}
+
+/// 图片最近下载失败，还没有到重试时间
+async fn is_failed(app_handle: &AppHandle, url: &str) -> Result<bool> {
+  let hash = url_hash(url);
+  let failed = app_handle
+    .db(move |db| {
+      db.query_row(
+        &format!(
+          "SELECT COUNT(*) FROM image_cache WHERE hash = ?4 AND {}",
+          RETRY_PENDING
+        ),
+        params![Local::now().timestamp(), RETRY_DELAY, MAX_RETRY_DELAY, hash],
+        |row| row.get::<_, i64>(0),
+      )
+    })
+    .await?;
+  Ok(failed > 0)
+}
+
+/// 在后台下载没有缓存的图片，缓存后发送 `app://media/cached` 事件。
+/// 同一张图片正在下载或最近下载失败时直接返回
+pub fn cache_later(app_handle: &AppHandle, url: Url, referer: Option<String>) {
+  let url = url.to_string();
+
+  {
+    let mut downloading = DOWNLOADING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if downloading.contains(&url) {
+      return;
+    }
+
+    downloading.push(url.clone());
+  }
+
+  let app_handle = app_handle.clone();
+
+  tauri::async_runtime::spawn(async move {
+    let result = match is_failed(&app_handle, &url).await {
+      Ok(true) => Ok(false),
+      Ok(false) => match Downloader::new(&app_handle).await {
+        Ok(downloader) => cache_image(&app_handle, &downloader, &url, referer.as_deref()).await,
+        Err(err) => Err(err),
+      },
+      Err(err) => Err(err),
+    };
+
+    DOWNLOADING
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .retain(|downloading| downloading != &url);
+
+    match result {
+      Ok(true) => app_handle.emit_event("app://media/cached", MediaCachedEvent { url }),
+      Ok(false) => {}
+      Err(err) => warn!("Failed to cache image {}: {:?}", url, err),
+    }
+  });
+}
@@ -1,63 +1,186 @@
+use std::future::Future;
+use std::sync::OnceLock;
 use std::vec;
 
+use anyhow::anyhow;
 use chrono::{Days, Local};
-use log::{info, trace};
+use log::{error, info, trace, warn};
+use regex::Regex;
+use rusqlite::functions::FunctionFlags;
 use rusqlite::types::Value;
 use rusqlite::{params, params_from_iter, Connection, OpenFlags, Result, Row};
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use tauri::{AppHandle, Manager, State};
+use tauri::{async_runtime::spawn_blocking, AppHandle, Manager};
 
 use crate::app_handle::get_app_handle;
-use crate::events::{ArticleReadEvent, SeedUnreadCountEvent};
-use crate::seed::{Article, Seed};
+use crate::cache::{cleanup, get_article_images};
+use crate::error::{self, Error};
+use crate::events::{
+  ArticleReadEvent, ArticleStarEvent, ArticleTagEvent, EmitEvent, SeedUnreadCountEvent,
+};
+use crate::migrations::upgrade_if_needed;
+use crate::mute::{MuteAction, MuteRule};
+use crate::pool::DbPool;
+use crate::rules::{Rule, RuleAction};
+use crate::scheduler::reschedule;
+use crate::seed::{Article, ChannelInfo, Folder, Seed, Tag};
+use crate::watch::{MatchField, MatchMode, Matcher, WatchRule};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// 查询文件夹（含子文件夹）下所有种子 ID 的子查询，唯一的参数为文件夹 ID
+macro_rules! folder_seeds {
+  () => {
+    "SELECT seeds.id FROM seeds WHERE folder_id IN (WITH RECURSIVE subtree(id) AS (SELECT ? UNION ALL SELECT folders.id FROM folders JOIN subtree ON folders.parent_id = subtree.id) SELECT id FROM subtree)"
+  };
+}
+
+/// 监视列表的虚拟种子 ID
+pub const WATCH_LIST_SEED_ID: i64 = -1;
+/// 星标文章的虚拟种子 ID
+pub const STARRED_SEED_ID: i64 = -2;
+/// 保存的搜索的虚拟种子 ID 起点，ID 为 n 的保存的搜索对应的虚拟种子 ID 为 `-1000 - n`
+pub const SAVED_SEARCH_SEED_ID_BASE: i64 = -1000;
+
+/// 查询文章时的公共列，附带种子名称和标签列表
+const ARTICLE_COLUMNS: &str = "articles.*, seeds.name, (SELECT json_group_array(tags.name) FROM article_tags JOIN tags ON article_tags.tag_id = tags.id WHERE article_tags.article_id = articles.id) AS tags";
+/// 查询文章时的公共表
+const ARTICLE_FROM: &str = "FROM articles LEFT JOIN seeds ON articles.seed_id = seeds.id";
 
-const CURRENT_DB_VERSION: u32 = 4;
+/// 只读连接的数量
+const READER_COUNT: usize = 4;
 
 pub struct AppState {
-  pub db: std::sync::Mutex<Option<Connection>>,
+  pub db: OnceLock<DbPool>,
+}
+
+/// 数据库操作的结果，数据库不可用时由 [`DbAccess`] 直接返回错误
+pub trait DbResult {
+  fn unavailable() -> Self;
+}
+
+impl<T, E: From<rusqlite::Error>> DbResult for std::result::Result<T, E> {
+  fn unavailable() -> Self {
+    Err(
+      rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+        Some("The database is not available.".to_string()),
+      )
+      .into(),
+    )
+  }
 }
 
+/// 数据库访问。异步版本在阻塞线程池上执行操作，不会占用异步运行时的工作线程，
+/// 因此不会阻塞其他命令和后台任务。
 pub trait DbAccess {
-  fn db<F, TResult>(&self, operation: F) -> TResult
+  /// 使用只读连接
+  fn db<F, TResult>(&self, operation: F) -> impl Future<Output = TResult> + Send
+  where
+    F: FnOnce(&Connection) -> TResult + Send + 'static,
+    TResult: DbResult + Send + 'static;
+
+  /// 使用写连接
+  fn db_mut<F, TResult>(&self, operation: F) -> impl Future<Output = TResult> + Send
+  where
+    F: FnOnce(&mut Connection) -> TResult + Send + 'static,
+    TResult: DbResult + Send + 'static;
+
+  /// 在当前线程上同步使用只读连接，只用于自定义协议等同步的上下文
+  fn db_sync<F, TResult>(&self, operation: F) -> TResult
   where
-    F: FnOnce(&Connection) -> TResult;
+    F: FnOnce(&Connection) -> TResult,
+    TResult: DbResult;
 
-  fn db_mut<F, TResult>(&self, operation: F) -> TResult
+  /// 在当前线程上同步使用写连接，只用于自定义协议等同步的上下文
+  fn db_mut_sync<F, TResult>(&self, operation: F) -> TResult
   where
-    F: FnOnce(&mut Connection) -> TResult;
+    F: FnOnce(&mut Connection) -> TResult,
+    TResult: DbResult;
 }
 
 impl DbAccess for AppHandle {
-  fn db<F, TResult>(&self, operation: F) -> TResult
+  fn db<F, TResult>(&self, operation: F) -> impl Future<Output = TResult> + Send
+  where
+    F: FnOnce(&Connection) -> TResult + Send + 'static,
+    TResult: DbResult + Send + 'static,
+  {
+    let app_handle = self.clone();
+    run_blocking(move || app_handle.db_sync(operation))
+  }
+
+  fn db_mut<F, TResult>(&self, operation: F) -> impl Future<Output = TResult> + Send
+  where
+    F: FnOnce(&mut Connection) -> TResult + Send + 'static,
+    TResult: DbResult + Send + 'static,
+  {
+    let app_handle = self.clone();
+    run_blocking(move || app_handle.db_mut_sync(operation))
+  }
+
+  fn db_sync<F, TResult>(&self, operation: F) -> TResult
   where
     F: FnOnce(&Connection) -> TResult,
+    TResult: DbResult,
   {
-    let app_state: State<AppState> = self.state();
-    let db_connection_guard = app_state.db.lock().unwrap();
-    let db = db_connection_guard.as_ref().unwrap();
+    let Some(app_state) = self.try_state::<AppState>() else {
+      return TResult::unavailable();
+    };
+    let Some(pool) = app_state.db.get() else {
+      return TResult::unavailable();
+    };
 
-    operation(db)
+    pool.read(operation)
   }
 
-  fn db_mut<F, TResult>(&self, operation: F) -> TResult
+  fn db_mut_sync<F, TResult>(&self, operation: F) -> TResult
   where
     F: FnOnce(&mut Connection) -> TResult,
+    TResult: DbResult,
   {
-    let app_state: State<AppState> = self.state();
-    let mut db_connection_guard = app_state.db.lock().unwrap();
-    let db = db_connection_guard.as_mut().unwrap();
+    let Some(app_state) = self.try_state::<AppState>() else {
+      return TResult::unavailable();
+    };
+    let Some(pool) = app_state.db.get() else {
+      return TResult::unavailable();
+    };
+
+    pool.write(operation)
+  }
+}
 
-    operation(db)
+/// 在阻塞线程池上执行数据库操作，操作 panic 时视为数据库不可用
+async fn run_blocking<F, TResult>(operation: F) -> TResult
+where
+  F: FnOnce() -> TResult + Send + 'static,
+  TResult: DbResult + Send + 'static,
+{
+  match spawn_blocking(operation).await {
+    Ok(result) => result,
+    Err(err) => {
+      error!("The database operation failed: {}", err);
+      TResult::unavailable()
+    }
   }
 }
 
-pub fn initialize(app_handle: &AppHandle, readonly: bool) -> Result<Connection> {
+/// 打开数据库连接池，写连接负责升级数据库
+pub fn open_pool(app_handle: &AppHandle) -> anyhow::Result<DbPool> {
+  let writer = initialize(app_handle, false)?;
+  let readers = (0..READER_COUNT)
+    .map(|_| initialize(app_handle, true))
+    .collect::<anyhow::Result<Vec<_>>>()?;
+
+  Ok(DbPool::new(writer, readers))
+}
+
+fn initialize(app_handle: &AppHandle, readonly: bool) -> anyhow::Result<Connection> {
   let app_dir = app_handle
     .path_resolver()
     .app_data_dir()
-    .expect("The app data directory should exist.");
-  std::fs::create_dir_all(&app_dir).expect("The app data directory should be created.");
+    .ok_or_else(|| anyhow!("The app data directory is unavailable."))?;
+  std::fs::create_dir_all(&app_dir)?;
   let sqlite_path = app_dir.join("rssrs.db");
 
   let flags = if readonly {
@@ -75,95 +198,106 @@ pub fn initialize(app_handle: &AppHandle, readonly: bool) -> Result<Connection>
     db.pragma_update(None, "optimize", 0x10002)?;
   }
 
-  let mut user_pragma = db.prepare("PRAGMA user_version")?;
-  let existing_user_version: u32 = user_pragma.query_row([], |row| Ok(row.get(0)?))?;
-  drop(user_pragma);
+  register_functions(&db)?;
 
-  upgrade_if_needed(&mut db, existing_user_version)?;
+  if !readonly {
+    let mut user_pragma = db.prepare("PRAGMA user_version")?;
+    let existing_user_version: u32 = user_pragma.query_row([], |row| Ok(row.get(0)?))?;
+    drop(user_pragma);
+
+    upgrade_if_needed(&mut db, existing_user_version)?;
+  }
 
   Ok(db)
 }
 
-/// Upgrades the database to the current version.
-fn upgrade_if_needed(db: &mut Connection, existing_version: u32) -> Result<()> {
-  if existing_version < CURRENT_DB_VERSION {
-    let tx = db.transaction()?;
-
-    tx.pragma_update(None, "user_version", CURRENT_DB_VERSION)?;
-
-    tx.execute_batch(
-      "
-      CREATE TABLE IF NOT EXISTS seeds (
-        id INTEGER PRIMARY KEY,
-        name TEXT NOT NULL UNIQUE,
-        url TEXT NOT NULL UNIQUE,
-        favicon TEXT,
-        interval INTEGER,
-        last_fetched_at INTEGER,
-        last_fetch_ok INTEGER
-      );
-      CREATE TABLE IF NOT EXISTS articles (
-        id INTEGER PRIMARY KEY,
-        seed_id INTEGER NOT NULL REFERENCES seeds (id) ON DELETE CASCADE ON UPDATE CASCADE,
-        guid TEXT NOT NULL UNIQUE,
-        title TEXT,
-        author TEXT,
-        desc TEXT,
-        link TEXT,
-        pub_date INTEGER NOT NULL,
-        unread INTEGER
-      );
-      CREATE INDEX IF NOT EXISTS articles_pub_date ON articles (pub_date DESC);
-      CREATE TABLE IF NOT EXISTS settings (
-        key TEXT PRIMARY KEY,
-        value TEXT NOT NULL
-      );
-      CREATE TABLE IF NOT EXISTS watch_list (
-        id INTEGER PRIMARY KEY,
-        keyword TEXT NOT NULL UNIQUE
-      );
-      PRAGMA optimize;
-      ",
-    )?;
-
-    tx.commit()?;
-  }
+/// 注册自定义 SQL 函数
+fn register_functions(db: &Connection) -> Result<()> {
+  // `X REGEXP Y` 即 `regexp(Y, X)`，编译后的正则表达式在同一语句中缓存
+  db.create_scalar_function(
+    "regexp",
+    2,
+    FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+    |ctx| {
+      let regex = ctx.get_or_create_aux(0, |vr| -> std::result::Result<_, BoxError> {
+        Ok(Regex::new(vr.as_str()?)?)
+      })?;
+      let text = ctx
+        .get_raw(1)
+        .as_str_or_null()
+        .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?;
+
+      Ok(text.is_some_and(|text| regex.is_match(text)))
+    },
+  )
+}
 
-  Ok(())
+/// 序列化为 JSON 文本，用于保存到 TEXT 列
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+  serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))
 }
 
 /// 执行数据库优化
-pub fn optimize() {
+pub async fn optimize() {
   let app_handle = get_app_handle();
 
   if let Some(app_handle) = app_handle {
-    let _ = app_handle.db(|db| -> Result<()> {
+    let handle = app_handle.clone();
+    let result = app_handle.db_mut(move |db| -> Result<()> {
       let now = Local::now();
-      let deadline = now.checked_sub_days(Days::new(30)).unwrap().timestamp();
-      db.execute(
-        "DELETE FROM articles WHERE unread = ?1 AND pub_date < ?2",
-        [0, deadline],
-      )?;
+
+      if let Some(deadline) = now.checked_sub_days(Days::new(30)) {
+        let ids = {
+          let mut stmt = db.prepare(
+            "SELECT id FROM articles WHERE (unread = 0 OR muted_by IS NOT NULL) AND starred = 0 AND pub_date < ?1",
+          )?;
+          let rows = stmt.query_map([deadline.timestamp()], |row| row.get::<_, i64>(0))?;
+          rows.collect::<Result<Vec<_>>>()?
+        };
+        let images = get_article_images(db, &ids).unwrap_or_else(|err| {
+          warn!("Failed to get images of old articles: {:?}", err);
+          Vec::new()
+        });
+
+        // 隐藏的文章保留了未读状态，过期后同样删除
+        db.execute(
+          "DELETE FROM articles WHERE (unread = 0 OR muted_by IS NOT NULL) AND starred = 0 AND pub_date < ?1",
+          [deadline.timestamp()],
+        )?;
+
+        // 删除清理的文章中不再被其他文章引用的图片缓存
+        if let Err(err) = cleanup(&handle, db, &images) {
+          warn!("Failed to clean up the image cache: {:?}", err);
+        }
+      }
+
       db.execute_batch("PRAGMA optimize; VACUUM; PRAGMA wal_checkpoint(truncate);")?;
       Ok(())
-    });
+    })
+    .await;
+
+    if let Err(err) = result {
+      warn!("Failed to optimize the database: {:?}", err);
+    }
   }
 }
 
 /// 插入种子。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_insert_seed(app_handle: AppHandle, name: String, url: String) -> bool {
-  let result = app_handle.db(|db| -> Result<()> {
-    let mut stmt = db.prepare("INSERT INTO seeds (name, url, interval, last_fetched_at, last_fetch_ok) VALUES (?1, ?2, 10, 0, 0)")?;
-    stmt.execute([name, url])?;
-
-    app_handle.emit_all("app://seed/add", ()).unwrap();
+pub async fn db_insert_seed(app_handle: AppHandle, name: String, url: String) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("INSERT INTO seeds (name, url, interval, last_fetched_at, last_fetch_ok) VALUES (?1, ?2, 10, 0, 0)")?;
+      stmt.execute([name, url])?;
+      Ok(())
+    })
+    .await?;
 
-    Ok(())
-  });
+  reschedule();
+  app_handle.emit_event("app://seed/add", ());
 
-  result.is_ok()
+  Ok(())
 }
 
 /// 更新种子。
@@ -174,17 +308,26 @@ pub async fn db_update_seed(
   seed_id: i64,
   name: String,
   url: String,
-) -> bool {
-  let result = app_handle.db(|db| -> Result<()> {
-    let mut stmt = db.prepare("UPDATE seeds SET name = ?1, url = ?2 WHERE id = ?3")?;
-    stmt.execute(params![name, url, seed_id])?;
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| {
+      // URL 改变后，原来的条件请求校验信息不再有效
+      update_seed(db, "UPDATE seeds SET name = ?1, url = ?2, etag = IIF(url = ?2, etag, NULL), last_modified = IIF(url = ?2, last_modified, NULL) WHERE id = ?3", params![name, url, seed_id])
+    })
+    .await?;
 
-    app_handle.emit_all("app://seed/add", ()).unwrap();
+  reschedule();
+  app_handle.emit_event("app://seed/add", ());
 
-    Ok(())
-  });
+  Ok(())
+}
 
-  result.is_ok()
+/// 修改种子，没有修改任何行说明种子不存在，返回 `QueryReturnedNoRows`
+fn update_seed<P: rusqlite::Params>(db: &Connection, sql: &str, params: P) -> Result<()> {
+  match db.execute(sql, params)? {
+    0 => Err(rusqlite::Error::QueryReturnedNoRows),
+    _ => Ok(()),
+  }
 }
 
 /// 将行转换为 Seed
@@ -197,6 +340,21 @@ fn to_seed(row: &Row) -> Result<Seed> {
     interval: row.get("interval")?,
     last_fetched_at: row.get("last_fetched_at")?,
     last_fetch_ok: row.get("last_fetch_ok")?,
+    last_fetch_error: row.get("last_fetch_error")?,
+    folder_id: row.get("folder_id")?,
+    notify: row.get("notify")?,
+    paused: row.get("paused")?,
+    channel: ChannelInfo {
+      title: row.get("channel_title")?,
+      description: row.get("channel_description")?,
+      link: row.get("channel_link")?,
+      language: row.get("channel_language")?,
+      image: row.get("channel_image")?,
+      generator: row.get("channel_generator")?,
+      last_build_date: row.get("channel_last_build_date")?,
+    },
+    auto_extract: row.get("auto_extract")?,
+    extract_selector: row.get("extract_selector")?,
   })
 }
 
@@ -216,25 +374,333 @@ pub fn get_all_seeds(db: &Connection) -> Result<Vec<Seed>> {
 /// 获取所有种子。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_get_all_seeds(app_handle: AppHandle) -> Vec<Seed> {
-  let result = app_handle.db(get_all_seeds);
+pub async fn db_get_all_seeds(app_handle: AppHandle) -> error::Result<Vec<Seed>> {
+  let result = app_handle.db(get_all_seeds).await;
 
-  if let Ok(items) = result {
-    items
-  } else {
-    vec![]
+  Ok(result?)
+}
+
+/// 设置种子的通知，`None` 表示跟随全局设置。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_set_seed_notify(
+  app_handle: AppHandle,
+  seed_id: i64,
+  notify: Option<bool>,
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| {
+      update_seed(
+        db,
+        "UPDATE seeds SET notify = ?1 WHERE id = ?2",
+        params![notify, seed_id],
+      )
+    })
+    .await?;
+
+  app_handle.emit_event("app://seed/add", ());
+
+  Ok(())
+}
+
+/// 暂停或恢复种子的定时抓取。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_set_seed_paused(
+  app_handle: AppHandle,
+  seed_id: i64,
+  paused: bool,
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| {
+      update_seed(
+        db,
+        "UPDATE seeds SET paused = ?1 WHERE id = ?2",
+        params![paused, seed_id],
+      )
+    })
+    .await?;
+
+  reschedule();
+  app_handle.emit_event("app://seed/add", ());
+
+  Ok(())
+}
+
+/// 设置种子的正文提取选项，`selector` 为空表示自动查找正文。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_set_seed_extract(
+  app_handle: AppHandle,
+  seed_id: i64,
+  auto_extract: bool,
+  selector: Option<String>,
+) -> error::Result<()> {
+  let selector = selector
+    .map(|selector| selector.trim().to_string())
+    .filter(|selector| !selector.is_empty());
+
+  if let Some(selector) = &selector {
+    if kuchikiki::Selectors::compile(selector).is_err() {
+      return Err(Error::validation(format!("Invalid selector: {}", selector)));
+    }
+  }
+
+  app_handle
+    .db_mut(move |db| {
+      update_seed(
+        db,
+        "UPDATE seeds SET auto_extract = ?1, extract_selector = ?2 WHERE id = ?3",
+        params![auto_extract, selector, seed_id],
+      )
+    })
+    .await?;
+
+  app_handle.emit_event("app://seed/add", ());
+
+  Ok(())
+}
+
+/// 移动种子到文件夹。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_move_seed(
+  app_handle: AppHandle,
+  seed_id: i64,
+  folder_id: Option<i64>,
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| {
+      update_seed(
+        db,
+        "UPDATE seeds SET folder_id = ?1 WHERE id = ?2",
+        params![folder_id, seed_id],
+      )
+    })
+    .await?;
+
+  app_handle.emit_event("app://seed/add", ());
+
+  Ok(())
+}
+
+/// 获取种子所在的文件夹及其所有上级文件夹的 ID
+pub fn get_seed_folder_ids(db: &Connection, seed_id: i64) -> Result<Vec<i64>> {
+  let mut stmt = db.prepare("WITH RECURSIVE ancestors(id) AS (SELECT folder_id FROM seeds WHERE id = ?1 AND folder_id IS NOT NULL UNION ALL SELECT folders.parent_id FROM folders JOIN ancestors ON folders.id = ancestors.id WHERE folders.parent_id IS NOT NULL) SELECT id FROM ancestors")?;
+  let mut rows = stmt.query([seed_id])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(row.get(0)?);
+  }
+
+  Ok(items)
+}
+
+/// 获取文件夹（含子文件夹）下所有种子的 ID
+pub fn get_folder_seed_ids(db: &Connection, folder_id: i64) -> Result<Vec<i64>> {
+  let mut stmt = db.prepare(folder_seeds!())?;
+  let mut rows = stmt.query([folder_id])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(row.get(0)?);
+  }
+
+  Ok(items)
+}
+
+/// 将行转换为 Folder
+fn to_folder(row: &Row) -> Result<Folder> {
+  Ok(Folder {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    parent_id: row.get("parent_id")?,
+  })
+}
+
+/// 获取所有文件夹。
+fn get_all_folders(db: &Connection) -> Result<Vec<Folder>> {
+  let mut stmt = db.prepare("SELECT * FROM folders ORDER BY name")?;
+  let mut rows = stmt.query([])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(to_folder(row)?);
+  }
+
+  Ok(items)
+}
+
+/// 获取所有文件夹。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_all_folders(app_handle: AppHandle) -> error::Result<Vec<Folder>> {
+  let result = app_handle.db(get_all_folders).await;
+
+  Ok(result?)
+}
+
+/// 创建文件夹。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_insert_folder(
+  app_handle: AppHandle,
+  name: String,
+  parent_id: Option<i64>,
+) -> error::Result<()> {
+  check_folder_name(&name)?;
+
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("INSERT INTO folders (name, parent_id) VALUES (?1, ?2)")?;
+      stmt.execute(params![name, parent_id])?;
+      Ok(())
+    })
+    .await?;
+
+  app_handle.emit_event("app://folder/change", ());
+
+  Ok(())
+}
+
+/// 重命名文件夹。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_rename_folder(
+  app_handle: AppHandle,
+  folder_id: i64,
+  name: String,
+) -> error::Result<()> {
+  check_folder_name(&name)?;
+
+  app_handle
+    .db_mut(move |db| -> error::Result<()> {
+      let mut stmt = db.prepare("UPDATE folders SET name = ?1 WHERE id = ?2")?;
+      check_folder_changed(stmt.execute(params![name, folder_id])?)
+    })
+    .await?;
+
+  app_handle.emit_event("app://folder/change", ());
+
+  Ok(())
+}
+
+/// 移动文件夹到另一个文件夹下。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_move_folder(
+  app_handle: AppHandle,
+  folder_id: i64,
+  parent_id: Option<i64>,
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> error::Result<()> {
+      if let Some(parent_id) = parent_id {
+        // 不能移动到自己或自己的子文件夹下
+        let mut stmt = db.prepare("WITH RECURSIVE subtree(id) AS (SELECT ?1 UNION ALL SELECT folders.id FROM folders JOIN subtree ON folders.parent_id = subtree.id) SELECT COUNT(*) FROM subtree WHERE id = ?2")?;
+        let count: i32 = stmt.query_row([folder_id, parent_id], |row| row.get(0))?;
+
+        if count > 0 {
+          return Err(Error::validation(
+            "A folder cannot be moved into itself or its subfolders.",
+          ));
+        }
+      }
+
+      let mut stmt = db.prepare("UPDATE folders SET parent_id = ?1 WHERE id = ?2")?;
+      check_folder_changed(stmt.execute(params![parent_id, folder_id])?)
+    })
+    .await?;
+
+  app_handle.emit_event("app://folder/change", ());
+
+  Ok(())
+}
+
+/// 删除文件夹，其中的种子和子文件夹移动到上级文件夹。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_delete_folder(app_handle: AppHandle, folder_id: i64) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> error::Result<()> {
+      let tx = db.transaction()?;
+
+      tx.execute(
+        "UPDATE seeds SET folder_id = (SELECT parent_id FROM folders WHERE id = ?1) WHERE folder_id = ?1",
+        [folder_id],
+      )?;
+      tx.execute(
+        "UPDATE folders SET parent_id = (SELECT parent_id FROM folders WHERE id = ?1) WHERE parent_id = ?1",
+        [folder_id],
+      )?;
+      // 文件夹不存在时回滚事务
+      check_folder_changed(tx.execute("DELETE FROM folders WHERE id = ?1", [folder_id])?)?;
+      tx.commit()?;
+      Ok(())
+    })
+    .await?;
+
+  app_handle.emit_event("app://folder/change", ());
+  app_handle.emit_event("app://seed/add", ());
+
+  Ok(())
+}
+
+/// 文件夹名称不能为空
+fn check_folder_name(name: &str) -> error::Result<()> {
+  if name.trim().is_empty() {
+    return Err(Error::validation("The folder name is empty."));
+  }
+
+  Ok(())
+}
+
+/// 没有修改任何行说明文件夹不存在
+fn check_folder_changed(count: usize) -> error::Result<()> {
+  match count {
+    0 => Err(Error::not_found("The folder does not exist.")),
+    _ => Ok(()),
   }
 }
 
+fn get_folder_unread_count(db: &Connection, folder_id: i64) -> Result<i32> {
+  let mut stmt = db.prepare(concat!(
+    "SELECT COUNT(*) FROM articles WHERE seed_id IN (",
+    folder_seeds!(),
+    ") AND unread != 0 AND muted_by IS NULL"
+  ))?;
+  stmt.query_row([folder_id], |row| row.get(0))
+}
+
+/// 获取文件夹未读数量。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_folder_unread_count(
+  app_handle: AppHandle,
+  folder_id: i64,
+) -> error::Result<i32> {
+  let result = app_handle
+    .db(move |db| get_folder_unread_count(db, folder_id))
+    .await;
+
+  Ok(result?)
+}
+
+/// 获取未读数量，被静音隐藏的文章不计入
 fn get_unread_count(db: &Connection, seed_id: Option<i64>) -> Result<i32> {
+  if let Some(seed) = seed_id.and_then(VirtualSeed::from_seed_id) {
+    return get_virtual_unread_count(db, seed);
+  }
+
   let (sql, params) = if let Some(seed_id) = seed_id {
     (
-      "SELECT COUNT(*) FROM articles WHERE seed_id = ?1 AND unread != 0",
+      "SELECT COUNT(*) FROM articles WHERE seed_id = ?1 AND unread != 0 AND muted_by IS NULL",
       [seed_id],
     )
   } else {
     (
-      "SELECT COUNT(*) FROM articles WHERE seed_id != ?1 AND unread != 0",
+      "SELECT COUNT(*) FROM articles WHERE seed_id != ?1 AND unread != 0 AND muted_by IS NULL",
       [0],
     )
   };
@@ -252,16 +718,15 @@ fn get_unread_count(db: &Connection, seed_id: Option<i64>) -> Result<i32> {
 /// 获取未读数量。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_get_unread_count(app_handle: AppHandle, seed_id: Option<i64>) -> i32 {
-  let result = app_handle.db(|db| -> Result<i32> { get_unread_count(db, seed_id) });
-
-  result.unwrap()
+pub async fn db_get_unread_count(
+  app_handle: AppHandle,
+  seed_id: Option<i64>,
+) -> error::Result<i32> {
+  let result = app_handle
+    .db(move |db| -> Result<i32> { get_unread_count(db, seed_id) })
+    .await;
 
-  // if let Ok(result) = result {
-  //   result
-  // } else {
-  //   Ok(0)
-  // }
+  Ok(result?)
 }
 
 /// 将行转换为 Seed
@@ -273,27 +738,74 @@ fn to_article(row: &Row) -> Result<Article> {
     title: row.get("title")?,
     author: row.get("author")?,
     desc: row.get("desc")?,
+    content: row.get("content")?,
     link: row.get("link")?,
     pub_date: row.get("pub_date")?,
     unread: row.get("unread")?,
+    starred: row.get("starred")?,
+    tags: serde_json::from_str(&row.get::<_, String>("tags")?).unwrap_or_default(),
+    snippet: row.get("snippet")?,
   })
 }
 
 fn get_article(db: &Connection, id: i64) -> Result<Article> {
-  let mut stmt = db.prepare("SELECT articles.*, seeds.name FROM articles LEFT JOIN seeds ON articles.seed_id = seeds.id WHERE articles.id = ?1")?;
-  let mut rows = stmt.query([id])?;
-  let row = rows.next()?;
-  let row = row.unwrap();
-  to_article(row)
+  let mut stmt = db.prepare(&format!(
+    "SELECT {}, NULL AS snippet {} WHERE articles.id = ?1",
+    ARTICLE_COLUMNS, ARTICLE_FROM
+  ))?;
+  stmt.query_row([id], to_article)
 }
 
-#[derive(Debug, Deserialize, Serialize, Type)]
+/// 已读状态过滤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadState {
+  /// 仅未读
+  Unread,
+  /// 仅已读
+  Read,
+  /// 全部
+  All,
+}
+
+/// 文章排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ArticleSort {
+  /// 最新的在前
+  #[default]
+  NewestFirst,
+  /// 最旧的在前
+  OldestFirst,
+  /// 按种子分组，组内最新的在前
+  BySeed,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct ArticleFilters {
   pub seed_id: Option<i64>,
+  pub folder_id: Option<i64>,
   pub cursor: Option<String>,
   pub limit: Option<i32>,
   pub search: Option<String>,
+  pub tag: Option<String>,
+  /// 同时查询多个种子
+  pub seed_ids: Option<Vec<i64>>,
+  /// 已读状态，默认只看未读；搜索或按标签过滤时默认看全部
+  pub read_state: Option<ReadState>,
+  /// 是否加了星标
+  pub starred: Option<bool>,
+  /// 作者，不区分大小写的部分匹配
+  pub author: Option<String>,
+  /// 发布时间下限（含），UNIX Epoch
+  pub since: Option<i64>,
+  /// 发布时间上限（不含），UNIX Epoch
+  pub until: Option<i64>,
+  /// 排序方式
+  pub sort: Option<ArticleSort>,
+  /// 为 `true` 时只看被静音隐藏的文章，否则不显示这些文章
+  pub muted: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Type)]
@@ -303,348 +815,1857 @@ pub struct ArticleResult {
   next_cursor: Option<String>,
 }
 
-fn get_articles_with(
-  db: &Connection,
-  filters: &ArticleFilters,
-  query: &Option<String>,
-  mut params: Vec<Value>,
-) -> Result<ArticleResult> {
-  let mut pub_date = i64::MAX;
-  let mut id = 0i64;
-  let limit = filters.limit.unwrap_or(20);
+/// 保存的搜索条件，各条件之间为“与”的关系
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQuery {
+  /// 种子
+  pub seed_ids: Option<Vec<i64>>,
+  /// 文件夹
+  pub folder_id: Option<i64>,
+  /// 关键字，FTS5 查询语法
+  pub search: Option<String>,
+  /// 发布时间下限（含），UNIX Epoch
+  pub since: Option<i64>,
+  /// 发布时间上限（不含），UNIX Epoch
+  pub until: Option<i64>,
+  /// 只看最近几天
+  pub days: Option<u32>,
+  /// 已读状态
+  pub read_state: Option<ReadState>,
+  /// 是否加了星标
+  pub starred: Option<bool>,
+  /// 标签
+  pub tag: Option<String>,
+  /// 作者
+  pub author: Option<String>,
+}
 
-  if let Some(cursor) = &filters.cursor {
-    // cursor 格式：pub_date:id
-    let splitted: Vec<&str> = cursor.split(':').collect();
-    pub_date = splitted[0].parse().unwrap_or(i64::MAX);
-    id = splitted[1].parse().unwrap_or(0);
+impl SavedQuery {
+  /// 转换为文章过滤条件
+  fn to_filters(&self) -> ArticleFilters {
+    // 同时指定了最近几天和时间下限时，取较晚者
+    let since = self
+      .days
+      .and_then(|days| Local::now().checked_sub_days(Days::new(days.into())))
+      .map(|date| date.timestamp())
+      .max(self.since);
+
+    ArticleFilters {
+      seed_ids: self.seed_ids.clone(),
+      folder_id: self.folder_id,
+      search: self.search.clone(),
+      since,
+      until: self.until,
+      read_state: Some(self.read_state.unwrap_or(ReadState::All)),
+      starred: self.starred,
+      tag: self.tag.clone(),
+      author: self.author.clone(),
+      ..Default::default()
+    }
   }
+}
 
-  params.insert(0, (limit + 1).into());
-  params.insert(0, Value::Integer(0)); // unread
-  params.insert(0, Value::Integer(id));
-  params.insert(0, Value::Integer(pub_date));
+/// 保存的搜索（智能文件夹）
+#[derive(Debug, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSearch {
+  /// ID
+  pub id: i64,
+  /// 作为虚拟种子时的 ID
+  pub seed_id: i64,
+  /// 名称
+  pub name: String,
+  /// 搜索条件
+  pub query: SavedQuery,
+}
 
-  let mut query = if let Some(q) = query {
-    format!("AND ({})", q)
-  } else {
-    String::default()
-  };
+/// 虚拟种子，使用负数 ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VirtualSeed {
+  /// 监视列表
+  WatchList,
+  /// 星标文章
+  Starred,
+  /// 保存的搜索
+  SavedSearch(i64),
+}
 
-  if let Some(search) = &filters.search {
-    if !search.is_empty() {
-      let search_query = format!(" AND instr(title, ?{}) > 0", params.len() + 1);
-      query.push_str(&search_query);
-      params.push(Value::Text(search.to_owned()));
-      params[2] = Value::Integer(-1); // read
+impl VirtualSeed {
+  fn from_seed_id(seed_id: i64) -> Option<Self> {
+    match seed_id {
+      WATCH_LIST_SEED_ID => Some(Self::WatchList),
+      STARRED_SEED_ID => Some(Self::Starred),
+      id if id < SAVED_SEARCH_SEED_ID_BASE => {
+        Some(Self::SavedSearch(SAVED_SEARCH_SEED_ID_BASE - id))
+      }
+      _ => None,
     }
   }
 
-  let sql = format!("SELECT articles.*, seeds.name FROM articles LEFT JOIN seeds ON articles.seed_id = seeds.id WHERE (pub_date < ?1 OR (pub_date = ?1 AND articles.id >= ?2)) AND unread != ?3 {} ORDER BY pub_date DESC, articles.id ASC LIMIT ?4", query);
-  let mut stmt = db.prepare(&sql)?;
+  /// 生成虚拟种子的基础查询，以及过滤条件中是否默认包含已读文章。
+  ///
+  /// 虚拟种子不会匹配任何文章时返回 `None`。
+  fn prepare(&self, db: &Connection) -> Result<Option<(ArticleQuery, bool)>> {
+    let mut query = ArticleQuery::default();
+
+    match self {
+      Self::WatchList => {
+        let rules = get_watch_rules(db)?;
+
+        if rules.is_empty() {
+          return Ok(None);
+        }
+
+        query.and_condition(Condition::any(
+          rules
+            .iter()
+            .map(|rule| matcher_condition(&rule.matcher))
+            .collect(),
+        ));
+        Ok(Some((query, false)))
+      }
+      Self::Starred => {
+        query.and("articles.starred != 0", []);
+        Ok(Some((query, true)))
+      }
+      Self::SavedSearch(id) => {
+        let Some(saved) = get_saved_search(db, *id)? else {
+          return Ok(None);
+        };
 
-  #[cfg(debug_assertions)]
-  {
-    trace!("SQL: {}", &sql);
-    trace!("PARAMS: {:?}", &params);
+        filter_query(db, &saved.query.to_filters(), &mut query, true);
+        Ok(Some((query, saved.query.read_state.is_some())))
+      }
+    }
   }
+}
 
-  let mut rows = stmt.query(params_from_iter(params))?;
-  let mut articles = Vec::new();
+/// 由静态 SQL 片段组合成的条件
+#[derive(Debug)]
+struct Condition {
+  sql: String,
+  params: Vec<Value>,
+}
 
-  while let Some(row) = rows.next()? {
-    articles.push(to_article(row)?);
+impl Condition {
+  fn new<P>(sql: &'static str, params: P) -> Self
+  where
+    P: IntoIterator<Item = Value>,
+  {
+    Self {
+      sql: sql.to_owned(),
+      params: params.into_iter().collect(),
+    }
   }
 
-  let next_cursor = if articles.len() > limit as usize {
-    let last = articles.pop().unwrap();
-    Some(format!("{}:{}", last.pub_date, last.id))
-  } else {
-    None
-  };
+  fn join(conditions: Vec<Condition>, separator: &str, empty: &str) -> Self {
+    if conditions.is_empty() {
+      return Self {
+        sql: empty.to_owned(),
+        params: vec![],
+      };
+    }
 
-  Ok(ArticleResult {
-    articles,
+    let mut sqls = Vec::new();
+    let mut params = Vec::new();
+
+    for condition in conditions {
+      sqls.push(condition.sql);
+      params.extend(condition.params);
+    }
+
+    Self {
+      sql: format!("({})", sqls.join(separator)),
+      params,
+    }
+  }
+
+  /// 以 OR 连接，为空时不匹配任何文章
+  fn any(conditions: Vec<Condition>) -> Self {
+    Self::join(conditions, " OR ", "0")
+  }
+
+  /// 以 AND 连接，为空时匹配所有文章
+  fn all(conditions: Vec<Condition>) -> Self {
+    Self::join(conditions, " AND ", "1")
+  }
+}
+
+/// 匹配条件对应的 SQL 条件
+fn matcher_condition(matcher: &Matcher) -> Condition {
+  let pattern = matcher.pattern();
+  let fields = matcher
+    .fields
+    .iter()
+    .map(|field| Condition::new(field.regexp_condition(), [Value::Text(pattern.clone())]))
+    .collect();
+  let mut conditions = vec![Condition::any(fields)];
+
+  if let Some(seed_id) = matcher.seed_id {
+    conditions.push(Condition::new(
+      "articles.seed_id = ?",
+      [Value::Integer(seed_id)],
+    ));
+  }
+
+  if let Some(folder_id) = matcher.folder_id {
+    conditions.push(Condition::new(
+      concat!("articles.seed_id IN (", folder_seeds!(), ")"),
+      [Value::Integer(folder_id)],
+    ));
+  }
+
+  Condition::all(conditions)
+}
+
+/// 文章查询条件。
+///
+/// SQL 片段只接受静态字符串，所有用户输入都作为参数绑定，参数一律使用匿名的 `?` 占位符，
+/// 按添加顺序绑定。全文搜索时会连接 `articles_fts`，其中有同名的列，
+/// 因此条件中的列一律写成 `articles.列名`。
+#[derive(Debug, Default)]
+struct ArticleQuery {
+  joins: Vec<&'static str>,
+  conditions: Vec<String>,
+  params: Vec<Value>,
+  /// 全文搜索表达式，最终以“与”的关系合并为一个 MATCH 条件
+  fts: Vec<String>,
+}
+
+impl ArticleQuery {
+  /// 添加条件
+  fn and<P>(&mut self, condition: &'static str, params: P) -> &mut Self
+  where
+    P: IntoIterator<Item = Value>,
+  {
+    self.conditions.push(condition.to_owned());
+    self.params.extend(params);
+    self
+  }
+
+  /// 添加 `column IN (?, ?, ...)` 条件
+  fn and_in(&mut self, column: &'static str, values: &[i64]) -> &mut Self {
+    let placeholders = vec!["?"; values.len()].join(", ");
+    self
+      .conditions
+      .push(format!("{} IN ({})", column, placeholders));
+    self
+      .params
+      .extend(values.iter().map(|v| Value::Integer(*v)));
+    self
+  }
+
+  /// 添加组合条件
+  fn and_condition(&mut self, condition: Condition) -> &mut Self {
+    self.conditions.push(condition.sql);
+    self.params.extend(condition.params);
+    self
+  }
+
+  /// 添加全文搜索条件
+  fn and_match(&mut self, fts_query: String) -> &mut Self {
+    self.fts.push(fts_query);
+    self
+  }
+
+  /// 是否进行了全文搜索
+  fn is_searching(&self) -> bool {
+    !self.fts.is_empty()
+  }
+
+  /// 生成 FROM 和 WHERE 子句及其参数
+  fn build(mut self) -> (String, Vec<Value>) {
+    if !self.fts.is_empty() {
+      let fts = self
+        .fts
+        .iter()
+        .map(|q| format!("({})", q))
+        .collect::<Vec<String>>()
+        .join(" AND ");
+      self
+        .joins
+        .push("JOIN articles_fts ON articles_fts.rowid = articles.id");
+      self.and("articles_fts MATCH ?", [Value::Text(fts)]);
+    }
+
+    let conditions = if self.conditions.is_empty() {
+      String::from("1")
+    } else {
+      self.conditions.join(" AND ")
+    };
+    let sql = format!(
+      "{} {} WHERE {}",
+      ARTICLE_FROM,
+      self.joins.join(" "),
+      conditions
+    );
+
+    (sql, self.params)
+  }
+}
+
+/// 转换为 FTS5 查询。
+///
+/// 支持 FTS5 的短语、前缀和布尔查询语法；语法错误时将每个词作为短语进行查询。
+fn to_fts_query(db: &Connection, search: &str) -> String {
+  let valid = db
+    .prepare("SELECT rowid FROM articles_fts WHERE articles_fts MATCH ?1 LIMIT 1")
+    .and_then(|mut stmt| stmt.exists([search]));
+
+  if valid.is_ok() {
+    search.to_owned()
+  } else {
+    search
+      .split_whitespace()
+      .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+      .collect::<Vec<String>>()
+      .join(" ")
+  }
+}
+
+/// 解析 cursor，格式为 `pub_date:id`，按种子排序时为 `seed_id:pub_date:id`
+fn parse_cursor(cursor: &str, sort: ArticleSort) -> Option<Vec<i64>> {
+  let parts = cursor
+    .split(':')
+    .map(|part| part.parse::<i64>().ok())
+    .collect::<Option<Vec<i64>>>()?;
+  let expected = if sort == ArticleSort::BySeed { 3 } else { 2 };
+
+  if parts.len() == expected {
+    Some(parts)
+  } else {
+    None
+  }
+}
+
+/// 将过滤条件（不含 cursor、数量和排序）添加到查询中。
+///
+/// `include_read` 为未指定已读状态时是否包含已读文章。
+fn filter_query(
+  db: &Connection,
+  filters: &ArticleFilters,
+  query: &mut ArticleQuery,
+  include_read: bool,
+) {
+  let mut include_read = include_read;
+
+  if let Some(seed_id) = filters.seed_id {
+    query.and("articles.seed_id = ?", [Value::Integer(seed_id)]);
+  } else if let Some(folder_id) = filters.folder_id {
+    query.and(
+      concat!("articles.seed_id IN (", folder_seeds!(), ")"),
+      [Value::Integer(folder_id)],
+    );
+  }
+
+  if let Some(search) = &filters.search {
+    if !search.is_empty() {
+      query.and_match(to_fts_query(db, search));
+      include_read = true;
+    }
+  }
+
+  if filters.muted.unwrap_or_default() {
+    query.and("articles.muted_by IS NOT NULL", []);
+    include_read = true;
+  } else {
+    query.and("articles.muted_by IS NULL", []);
+  }
+
+  if let Some(tag) = &filters.tag {
+    query.and(
+      "articles.id IN (SELECT article_id FROM article_tags JOIN tags ON article_tags.tag_id = tags.id WHERE tags.name = ?)",
+      [Value::Text(tag.to_owned())],
+    );
+    include_read = true;
+  }
+
+  if let Some(seed_ids) = &filters.seed_ids {
+    if !seed_ids.is_empty() {
+      query.and_in("articles.seed_id", seed_ids);
+    }
+  }
+
+  let read_state = filters.read_state.unwrap_or(if include_read {
+    ReadState::All
+  } else {
+    ReadState::Unread
+  });
+
+  match read_state {
+    ReadState::Unread => {
+      query.and("articles.unread != 0", []);
+    }
+    ReadState::Read => {
+      query.and("articles.unread = 0", []);
+    }
+    ReadState::All => {}
+  }
+
+  if let Some(starred) = filters.starred {
+    query.and("articles.starred = ?", [Value::Integer(starred.into())]);
+  }
+
+  if let Some(author) = &filters.author {
+    if !author.is_empty() {
+      query.and(
+        "instr(lower(articles.author), lower(?)) > 0",
+        [Value::Text(author.to_owned())],
+      );
+    }
+  }
+
+  if let Some(since) = filters.since {
+    query.and("articles.pub_date >= ?", [Value::Integer(since)]);
+  }
+
+  if let Some(until) = filters.until {
+    query.and("articles.pub_date < ?", [Value::Integer(until)]);
+  }
+}
+
+fn get_articles_with(
+  db: &Connection,
+  filters: &ArticleFilters,
+  mut query: ArticleQuery,
+  include_read: bool,
+) -> Result<ArticleResult> {
+  let limit = filters.limit.unwrap_or(20);
+  let sort = filters.sort.unwrap_or_default();
+
+  filter_query(db, filters, &mut query, include_read);
+
+  // cursor 所指的文章包含在本页中
+  if let Some(cursor) = filters
+    .cursor
+    .as_deref()
+    .and_then(|c| parse_cursor(c, sort))
+  {
+    match sort {
+      ArticleSort::NewestFirst => query.and(
+        "(articles.pub_date < ? OR (articles.pub_date = ? AND articles.id >= ?))",
+        [cursor[0], cursor[0], cursor[1]].map(Value::Integer),
+      ),
+      ArticleSort::OldestFirst => query.and(
+        "(articles.pub_date > ? OR (articles.pub_date = ? AND articles.id >= ?))",
+        [cursor[0], cursor[0], cursor[1]].map(Value::Integer),
+      ),
+      ArticleSort::BySeed => query.and(
+        "(articles.seed_id > ? OR (articles.seed_id = ? AND (articles.pub_date < ? OR (articles.pub_date = ? AND articles.id >= ?))))",
+        [cursor[0], cursor[0], cursor[1], cursor[1], cursor[2]].map(Value::Integer),
+      ),
+    };
+  }
+
+  let order_by = match sort {
+    ArticleSort::NewestFirst => "articles.pub_date DESC, articles.id ASC",
+    ArticleSort::OldestFirst => "articles.pub_date ASC, articles.id ASC",
+    ArticleSort::BySeed => "articles.seed_id ASC, articles.pub_date DESC, articles.id ASC",
+  };
+  let snippet = if query.is_searching() {
+    "snippet(articles_fts, -1, '<mark>', '</mark>', '…', 16)"
+  } else {
+    "NULL"
+  };
+
+  let (from_where, mut params) = query.build();
+  let sql = format!(
+    "SELECT {}, {} AS snippet {} ORDER BY {} LIMIT ?",
+    ARTICLE_COLUMNS, snippet, from_where, order_by
+  );
+  params.push((limit + 1).into());
+  let mut stmt = db.prepare(&sql)?;
+
+  #[cfg(debug_assertions)]
+  {
+    trace!("SQL: {}", &sql);
+    trace!("PARAMS: {:?}", &params);
+  }
+
+  let mut rows = stmt.query(params_from_iter(params))?;
+  let mut articles = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    articles.push(to_article(row)?);
+  }
+
+  let next_cursor = if articles.len() > limit as usize {
+    articles.pop().map(|last| {
+      if sort == ArticleSort::BySeed {
+        format!("{}:{}:{}", last.seed_id, last.pub_date, last.id)
+      } else {
+        format!("{}:{}", last.pub_date, last.id)
+      }
+    })
+  } else {
+    None
+  };
+
+  Ok(ArticleResult {
+    articles,
     next_cursor,
   })
 }
 
-fn get_watched_articles(db: &Connection, filters: &ArticleFilters) -> Result<ArticleResult> {
-  let keywords = get_watch_list(db)?;
+fn get_articles(db: &Connection, filters: &ArticleFilters) -> Result<ArticleResult> {
+  get_articles_with(db, filters, ArticleQuery::default(), false)
+}
+
+fn get_virtual_articles(
+  db: &Connection,
+  seed: VirtualSeed,
+  filters: &ArticleFilters,
+) -> Result<ArticleResult> {
+  let Some((query, include_read)) = seed.prepare(db)? else {
+    return Ok(ArticleResult {
+      articles: vec![],
+      next_cursor: None,
+    });
+  };
+
+  let filters = ArticleFilters {
+    seed_id: None,
+    ..filters.clone()
+  };
+
+  get_articles_with(db, &filters, query, include_read)
+}
+
+/// 从指定的文章中筛选出命中监视列表的
+pub fn get_watched_ids(db: &Connection, ids: &[i64]) -> Result<Vec<i64>> {
+  let Some((mut query, _)) = VirtualSeed::WatchList.prepare(db)? else {
+    return Ok(vec![]);
+  };
+
+  query.and_in("articles.id", ids);
+
+  let (from_where, params) = query.build();
+  let mut stmt = db.prepare(&format!("SELECT articles.id {}", from_where))?;
+  let mut rows = stmt.query(params_from_iter(params))?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(row.get(0)?);
+  }
+
+  Ok(items)
+}
+
+/// 获取虚拟种子的未读数量
+fn get_virtual_unread_count(db: &Connection, seed: VirtualSeed) -> Result<i32> {
+  let Some((mut query, include_read)) = seed.prepare(db)? else {
+    return Ok(0);
+  };
+
+  let filters = ArticleFilters {
+    read_state: Some(ReadState::Unread),
+    ..Default::default()
+  };
+  filter_query(db, &filters, &mut query, include_read);
+
+  let (from_where, params) = query.build();
+  let mut stmt = db.prepare(&format!("SELECT COUNT(*) {}", from_where))?;
+  stmt.query_row(params_from_iter(params), |row| row.get(0))
+}
+
+/// 将虚拟种子的文章全部标记为已读
+fn read_virtual_all(db: &Connection, seed: VirtualSeed) -> Result<()> {
+  let Some((mut query, include_read)) = seed.prepare(db)? else {
+    return Ok(());
+  };
+
+  filter_query(db, &ArticleFilters::default(), &mut query, include_read);
+
+  let (from_where, params) = query.build();
+  let sql = format!(
+    "UPDATE articles SET unread = 0 WHERE id IN (SELECT articles.id {})",
+    from_where
+  );
+  db.execute(&sql, params_from_iter(params))?;
+  Ok(())
+}
+
+fn to_saved_search(row: &Row) -> Result<SavedSearch> {
+  let id: i64 = row.get("id")?;
+  let query: String = row.get("query")?;
+
+  Ok(SavedSearch {
+    id,
+    seed_id: SAVED_SEARCH_SEED_ID_BASE - id,
+    name: row.get("name")?,
+    query: serde_json::from_str(&query).unwrap_or_default(),
+  })
+}
+
+fn get_saved_search(db: &Connection, id: i64) -> Result<Option<SavedSearch>> {
+  let mut stmt = db.prepare("SELECT * FROM saved_searches WHERE id = ?1")?;
+  let mut rows = stmt.query([id])?;
+
+  if let Some(row) = rows.next()? {
+    Ok(Some(to_saved_search(row)?))
+  } else {
+    Ok(None)
+  }
+}
+
+fn get_saved_searches(db: &Connection) -> Result<Vec<SavedSearch>> {
+  let mut stmt = db.prepare("SELECT * FROM saved_searches ORDER BY name")?;
+  let mut rows = stmt.query([])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(to_saved_search(row)?);
+  }
+
+  Ok(items)
+}
+
+/// 获取所有保存的搜索。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_saved_searches(app_handle: AppHandle) -> error::Result<Vec<SavedSearch>> {
+  let result = app_handle.db(get_saved_searches).await;
+
+  Ok(result?)
+}
+
+/// 保存搜索。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_insert_saved_search(
+  app_handle: AppHandle,
+  name: String,
+  query: SavedQuery,
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let query = to_json(&query)?;
+      db.execute(
+        "INSERT INTO saved_searches (name, query) VALUES (?1, ?2)",
+        [name, query],
+      )?;
+      Ok(())
+    })
+    .await?;
+
+  app_handle.emit_event("app://search/change", ());
+
+  Ok(())
+}
+
+/// 修改保存的搜索。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_update_saved_search(
+  app_handle: AppHandle,
+  search_id: i64,
+  name: String,
+  query: SavedQuery,
+) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let query = to_json(&query)?;
+      db.execute(
+        "UPDATE saved_searches SET name = ?1, query = ?2 WHERE id = ?3",
+        params![name, query, search_id],
+      )?;
+      Ok(())
+    })
+    .await?;
+
+  app_handle.emit_event("app://search/change", ());
+
+  Ok(())
+}
+
+/// 删除保存的搜索。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_delete_saved_search(app_handle: AppHandle, search_id: i64) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| db.execute("DELETE FROM saved_searches WHERE id = ?1", [search_id]))
+    .await?;
+
+  app_handle.emit_event("app://search/change", ());
+
+  Ok(())
+}
+
+/// 获取单篇文章。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_article(app_handle: AppHandle, article_id: i64) -> error::Result<Article> {
+  let result = app_handle.db(move |db| get_article(db, article_id)).await;
+
+  Ok(result?)
+}
+
+/// 获取文章。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_articles(
+  app_handle: AppHandle,
+  filters: ArticleFilters,
+) -> error::Result<ArticleResult> {
+  let result = app_handle
+    .db(move |db| -> Result<ArticleResult> {
+      if let Some(seed) = filters.seed_id.and_then(VirtualSeed::from_seed_id) {
+        get_virtual_articles(db, seed, &filters)
+      } else {
+        get_articles(db, &filters)
+      }
+    })
+    .await;
+
+  Ok(result?)
+}
+
+/// 将文章标记为已读或未读。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_read_article(app_handle: AppHandle, item_id: i64, read: bool) -> error::Result<()> {
+  info!("Mark as read: {item_id}, {read}");
+
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("UPDATE articles SET unread = ?2 WHERE id = ?1")?;
+      stmt.execute(params![item_id, !read])?;
+
+      // 上报种子未读数量事件
+      let article = get_article(db, item_id)?;
+      let unread_count = get_unread_count(db, Some(article.seed_id))?;
+      handle.emit_event(
+        "app://seed/unread",
+        SeedUnreadCountEvent {
+          id: Some(article.seed_id),
+          unread_count,
+        },
+      );
+
+      let unread_count = get_unread_count(db, None)?;
+      handle.emit_event(
+        "app://seed/unread",
+        SeedUnreadCountEvent {
+          id: None,
+          unread_count,
+        },
+      );
+      Ok(())
+    })
+    .await?;
+
+  // 上报文章已读事件
+  app_handle.emit_event(
+    "app://article/unread",
+    ArticleReadEvent {
+      id: item_id,
+      unread: !read,
+    },
+  );
+
+  Ok(())
+}
+
+/// 为文章加星标或取消星标。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_star_article(
+  app_handle: AppHandle,
+  item_id: i64,
+  starred: bool,
+) -> error::Result<()> {
+  info!("Star: {item_id}, {starred}");
+
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("UPDATE articles SET starred = ?2 WHERE id = ?1")?;
+      stmt.execute(params![item_id, starred])?;
+      Ok(())
+    })
+    .await?;
+
+  // 上报文章星标事件
+  app_handle.emit_event(
+    "app://article/star",
+    ArticleStarEvent {
+      id: item_id,
+      starred,
+    },
+  );
+
+  Ok(())
+}
+
+/// 全部标记为已读
+#[tauri::command]
+#[specta::specta]
+pub async fn db_read_all(app_handle: AppHandle, seed_id: Option<i64>) -> error::Result<()> {
+  let sid = seed_id.unwrap_or_default();
+  info!("Read all: {sid}");
+
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      if let Some(seed) = VirtualSeed::from_seed_id(sid) {
+        read_virtual_all(db, seed)?;
+
+        handle.emit_event(
+          "app://seed/unread",
+          SeedUnreadCountEvent {
+            id: None,
+            unread_count: get_unread_count(db, None)?,
+          },
+        );
+        handle.emit_event(
+          "app://article/unread",
+          ArticleReadEvent {
+            id: -1,
+            unread: false,
+          },
+        );
+
+        return Ok(());
+      }
+
+      let (sql, params) = if sid > 0 {
+        (
+          "UPDATE articles SET unread = ?1 WHERE seed_id = ?2 AND muted_by IS NULL",
+          vec![0, sid],
+        )
+      } else {
+        (
+          "UPDATE articles SET unread = ?1 WHERE muted_by IS NULL",
+          vec![0],
+        )
+      };
+
+      let mut stmt = db.prepare(sql)?;
+      stmt.execute(params_from_iter(params))?;
+
+      // 上报种子未读数量事件
+      handle.emit_event(
+        "app://seed/unread",
+        SeedUnreadCountEvent {
+          id: seed_id,
+          unread_count: 0,
+        },
+      );
+
+      handle.emit_event(
+        "app://seed/unread",
+        SeedUnreadCountEvent {
+          id: None,
+          unread_count: if sid > 0 {
+            get_unread_count(db, None)?
+          } else {
+            0
+          },
+        },
+      );
+
+      handle.emit_event(
+        "app://article/unread",
+        ArticleReadEvent {
+          id: -1,
+          unread: false,
+        },
+      );
+
+      Ok(())
+    })
+    .await?;
+
+  Ok(())
+}
+
+/// 将文件夹（含子文件夹）中的文章全部标记为已读
+#[tauri::command]
+#[specta::specta]
+pub async fn db_read_folder(app_handle: AppHandle, folder_id: i64) -> error::Result<()> {
+  info!("Read folder: {folder_id}");
+
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let seed_ids = get_folder_seed_ids(db, folder_id)?;
+      db.execute(
+        concat!(
+          "UPDATE articles SET unread = 0 WHERE seed_id IN (",
+          folder_seeds!(),
+          ") AND muted_by IS NULL"
+        ),
+        [folder_id],
+      )?;
+
+      // 上报种子未读数量事件
+      for seed_id in seed_ids {
+        handle.emit_event(
+          "app://seed/unread",
+          SeedUnreadCountEvent {
+            id: Some(seed_id),
+            unread_count: 0,
+          },
+        );
+      }
+
+      handle.emit_event(
+        "app://seed/unread",
+        SeedUnreadCountEvent {
+          id: None,
+          unread_count: get_unread_count(db, None)?,
+        },
+      );
+
+      Ok(())
+    })
+    .await?;
+
+  Ok(())
+}
+
+fn get_article_tags(db: &Connection, item_id: i64) -> Result<Vec<String>> {
+  let mut stmt = db.prepare("SELECT tags.name FROM article_tags JOIN tags ON article_tags.tag_id = tags.id WHERE article_tags.article_id = ?1 ORDER BY tags.name")?;
+  let mut rows = stmt.query([item_id])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(row.get(0)?);
+  }
+
+  Ok(items)
+}
+
+/// 上报文章标签事件
+fn emit_article_tags(app_handle: &AppHandle, db: &Connection, item_id: i64) -> Result<()> {
+  let tags = get_article_tags(db, item_id)?;
+  app_handle.emit_event("app://article/tag", ArticleTagEvent { id: item_id, tags });
+  app_handle.emit_event("app://tag/change", ());
+  Ok(())
+}
+
+/// 为文章添加标签，标签不存在时创建
+pub fn tag_article(db: &Connection, item_id: i64, tag: &str) -> Result<()> {
+  db.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+  db.execute(
+    "INSERT OR IGNORE INTO article_tags (article_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+    params![item_id, tag],
+  )?;
+  Ok(())
+}
+
+/// 为文章添加标签。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_tag_article(app_handle: AppHandle, item_id: i64, tag: String) -> error::Result<()> {
+  info!("Tag: {item_id}, {tag}");
+
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let tx = db.transaction()?;
+      tag_article(&tx, item_id, &tag)?;
+      tx.commit()?;
+
+      emit_article_tags(&handle, db, item_id)
+    })
+    .await?;
+
+  Ok(())
+}
+
+/// 移除文章的标签。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_untag_article(
+  app_handle: AppHandle,
+  item_id: i64,
+  tag: String,
+) -> error::Result<()> {
+  info!("Untag: {item_id}, {tag}");
+
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      db.execute(
+        "DELETE FROM article_tags WHERE article_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+        params![item_id, tag],
+      )?;
+
+      emit_article_tags(&handle, db, item_id)
+    })
+    .await?;
+
+  Ok(())
+}
+
+fn get_tags(db: &Connection) -> Result<Vec<Tag>> {
+  let mut stmt = db.prepare("SELECT tags.id, tags.name, COUNT(article_tags.article_id) AS article_count FROM tags LEFT JOIN article_tags ON tags.id = article_tags.tag_id GROUP BY tags.id ORDER BY tags.name")?;
+  let mut rows = stmt.query([])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(Tag {
+      id: row.get("id")?,
+      name: row.get("name")?,
+      article_count: row.get("article_count")?,
+    });
+  }
+
+  Ok(items)
+}
+
+/// 获取所有标签及其文章数量。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_tags(app_handle: AppHandle) -> error::Result<Vec<Tag>> {
+  let result = app_handle.db(get_tags).await;
+
+  Ok(result?)
+}
+
+/// 删除标签。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_delete_tag(app_handle: AppHandle, tag: String) -> error::Result<()> {
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      db.execute("DELETE FROM tags WHERE name = ?1", [tag])?;
+
+      handle.emit_event("app://tag/change", ());
+
+      Ok(())
+    })
+    .await?;
+
+  Ok(())
+}
+
+fn get_watch_list(db: &Connection) -> Result<Vec<String>> {
+  let mut stmt = db.prepare("SELECT keyword FROM watch_list")?;
+  let mut rows = stmt.query([])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    let keyword: String = row.get(0)?;
+    items.push(keyword);
+  }
+
+  Ok(items)
+}
+
+/// 将监视规则和静音规则共有的列转换为 Matcher
+fn to_matcher(row: &Row) -> Result<Matcher> {
+  let match_mode: String = row.get("match_mode")?;
+  let fields: String = row.get("fields")?;
+
+  Ok(Matcher {
+    keyword: row.get("keyword")?,
+    match_mode: MatchMode::parse(&match_mode),
+    case_sensitive: row.get("case_sensitive")?,
+    fields: serde_json::from_str(&fields).unwrap_or_else(|_| vec![MatchField::Title]),
+    seed_id: row.get("seed_id")?,
+    folder_id: row.get("folder_id")?,
+  })
+}
+
+fn to_watch_rule(row: &Row) -> Result<WatchRule> {
+  Ok(WatchRule {
+    id: row.get("id")?,
+    matcher: to_matcher(row)?,
+  })
+}
+
+pub fn get_watch_rules(db: &Connection) -> Result<Vec<WatchRule>> {
+  let mut stmt = db.prepare("SELECT * FROM watch_list")?;
+  let mut rows = stmt.query([])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(to_watch_rule(row)?);
+  }
+
+  Ok(items)
+}
+
+/// 获取监视关键字列表。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_watch_list(app_handle: AppHandle) -> error::Result<Vec<String>> {
+  let result = app_handle.db(get_watch_list).await;
+
+  Ok(result?)
+}
+
+/// 添加监视关键字。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_add_watch_keyword(app_handle: AppHandle, keyword: String) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("INSERT INTO watch_list (keyword) VALUES (?1)")?;
+      stmt.execute([keyword])?;
+      Ok(())
+    })
+    .await?;
+
+  app_handle.emit_event("app://watchlist/change", ());
+
+  Ok(())
+}
+
+/// 获取监视规则列表。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_get_watch_rules(app_handle: AppHandle) -> error::Result<Vec<WatchRule>> {
+  let result = app_handle.db(get_watch_rules).await;
 
-  if keywords.is_empty() {
-    return Ok(ArticleResult {
-      articles: vec![],
-      next_cursor: None,
-    });
-  }
+  Ok(result?)
+}
 
-  let mut conds = Vec::new();
+/// 插入或更新监视规则，`id` 为 0 时插入
+fn save_watch_rule(db: &Connection, rule: &WatchRule) -> error::Result<()> {
+  if !rule.matcher.is_valid() {
+    return Err(Error::validation("The watch rule is invalid."));
+  }
 
-  for n in 0..keywords.len() {
-    conds.push(format!("instr(title, ?{}) > 0", n + 5));
+  let matcher = &rule.matcher;
+  let fields = to_json(&matcher.fields)?;
+  let params = params![
+    matcher.keyword,
+    matcher.match_mode.as_str(),
+    matcher.case_sensitive,
+    fields,
+    matcher.seed_id,
+    matcher.folder_id,
+    rule.id,
+  ];
+
+  if rule.id > 0 {
+    db.execute("UPDATE watch_list SET keyword = ?1, match_mode = ?2, case_sensitive = ?3, fields = ?4, seed_id = ?5, folder_id = ?6 WHERE id = ?7", params)?;
+  } else {
+    db.execute("INSERT INTO watch_list (keyword, match_mode, case_sensitive, fields, seed_id, folder_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)", &params[..6])?;
   }
 
-  let mut params: Vec<Value> = Vec::new();
+  Ok(())
+}
+
+/// 添加或修改监视规则，`id` 为 0 时添加。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_save_watch_rule(app_handle: AppHandle, rule: WatchRule) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| save_watch_rule(db, &rule))
+    .await?;
 
-  for keyword in keywords {
-    params.push(keyword.into());
-  }
+  app_handle.emit_event("app://watchlist/change", ());
 
-  get_articles_with(db, filters, &Some(conds.join(" OR ")), params)
+  Ok(())
 }
 
-fn get_articles(db: &Connection, filters: &ArticleFilters) -> Result<ArticleResult> {
-  let (query, params) = if let Some(seed_id) = filters.seed_id {
-    (
-      Some(String::from("seed_id = ?5")),
-      vec![Value::Integer(seed_id)],
-    )
-  } else {
-    (None, vec![])
-  };
+/// 删除监视规则。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_delete_watch_rule(app_handle: AppHandle, rule_id: i64) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| db.execute("DELETE FROM watch_list WHERE id = ?1", [rule_id]))
+    .await?;
 
-  get_articles_with(db, filters, &query, params)
+  app_handle.emit_event("app://watchlist/change", ());
+
+  Ok(())
 }
 
-/// 获取文章。
+/// 删除监视关键字。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_get_articles(app_handle: AppHandle, filters: ArticleFilters) -> ArticleResult {
-  let result = app_handle.db(|db| -> Result<ArticleResult> {
-    if let Some(seed_id) = filters.seed_id {
-      if seed_id < 0 {
-        return get_watched_articles(db, &filters);
-      }
-    }
+pub async fn db_delete_watch_keyword(app_handle: AppHandle, keyword: String) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("DELETE FROM watch_list WHERE keyword = ?1")?;
+      stmt.execute([keyword])?;
+      Ok(())
+    })
+    .await?;
 
-    get_articles(db, &filters)
-  });
+  app_handle.emit_event("app://watchlist/change", ());
 
-  result.unwrap()
+  Ok(())
+}
 
-  // if let Ok(result) = result {
-  //   result
-  // } else {
-  //   ItemResult {
-  //     articles: vec![],
-  //     next_cursor: None,
-  //   }
-  // }
+fn to_mute_rule(row: &Row) -> Result<MuteRule> {
+  let action: String = row.get("action")?;
+
+  Ok(MuteRule {
+    id: row.get("id")?,
+    action: MuteAction::parse(&action),
+    muted_count: row.get("muted_count")?,
+    matcher: to_matcher(row)?,
+  })
 }
 
-/// 将文章标记为已读或未读。
+pub fn get_mute_rules(db: &Connection) -> Result<Vec<MuteRule>> {
+  let mut stmt = db.prepare("SELECT * FROM mute_rules ORDER BY id")?;
+  let mut rows = stmt.query([])?;
+  let mut items = Vec::new();
+
+  while let Some(row) = rows.next()? {
+    items.push(to_mute_rule(row)?);
+  }
+
+  Ok(items)
+}
+
+/// 获取静音规则列表。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_read_article(app_handle: AppHandle, item_id: i64, read: bool) -> bool {
-  info!("Mark as read: {item_id}, {read}");
+pub async fn db_get_mute_rules(app_handle: AppHandle) -> error::Result<Vec<MuteRule>> {
+  let result = app_handle.db(get_mute_rules).await;
 
-  let result = app_handle.db(|db| -> Result<()> {
-    let mut stmt = db.prepare("UPDATE articles SET unread = ?2 WHERE id = ?1")?;
-    stmt.execute(params![item_id, !read])?;
+  Ok(result?)
+}
 
-    // 上报种子未读数量事件
-    let article = get_article(db, item_id)?;
-    let unread_count = get_unread_count(db, Some(article.seed_id))?;
-    app_handle
-      .emit_all(
-        "app://seed/unread",
-        SeedUnreadCountEvent {
-          id: Some(article.seed_id),
-          unread_count,
-        },
-      )
-      .unwrap();
+/// 插入或更新静音规则，`id` 为 0 时插入
+fn save_mute_rule(db: &Connection, rule: &MuteRule) -> error::Result<()> {
+  if !rule.matcher.is_valid() {
+    return Err(Error::validation("The mute rule is invalid."));
+  }
 
-    let unread_count = get_unread_count(db, None)?;
-    app_handle
-      .emit_all(
-        "app://seed/unread",
-        SeedUnreadCountEvent {
-          id: None,
-          unread_count,
-        },
-      )
-      .unwrap();
+  let matcher = &rule.matcher;
+  let fields = to_json(&matcher.fields)?;
+  let params = params![
+    matcher.keyword,
+    matcher.match_mode.as_str(),
+    matcher.case_sensitive,
+    fields,
+    matcher.seed_id,
+    matcher.folder_id,
+    rule.action.as_str(),
+    rule.id,
+  ];
+
+  if rule.id > 0 {
+    db.execute("UPDATE mute_rules SET keyword = ?1, match_mode = ?2, case_sensitive = ?3, fields = ?4, seed_id = ?5, folder_id = ?6, action = ?7 WHERE id = ?8", params)?;
+  } else {
+    db.execute("INSERT INTO mute_rules (keyword, match_mode, case_sensitive, fields, seed_id, folder_id, action) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)", &params[..7])?;
+  }
 
-    // 上报文章已读事件
-    app_handle
-      .emit_all(
-        "app://article/unread",
-        ArticleReadEvent {
-          id: item_id,
-          unread: !read,
-        },
-      )
-      .unwrap();
+  Ok(())
+}
 
-    Ok(())
-  });
+/// 添加或修改静音规则，`id` 为 0 时添加。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_save_mute_rule(app_handle: AppHandle, rule: MuteRule) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| save_mute_rule(db, &rule))
+    .await?;
 
-  result.unwrap();
-  true
+  app_handle.emit_event("app://mute/change", ());
+
+  Ok(())
 }
 
-/// 全部标记为已读
+/// 删除静音规则，被其隐藏的文章重新显示。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_read_all(app_handle: AppHandle, seed_id: Option<i64>) -> bool {
-  let sid = seed_id.unwrap_or_default();
-  info!("Read all: {sid}");
+pub async fn db_delete_mute_rule(app_handle: AppHandle, rule_id: i64) -> error::Result<()> {
+  let unread_count = app_handle
+    .db_mut(move |db| -> Result<i32> {
+      db.execute("DELETE FROM mute_rules WHERE id = ?1", [rule_id])?;
+      get_unread_count(db, None)
+    })
+    .await?;
+
+  app_handle.emit_event("app://mute/change", ());
+  app_handle.emit_event(
+    "app://seed/unread",
+    SeedUnreadCountEvent {
+      id: None,
+      unread_count,
+    },
+  );
 
-  let result = app_handle.db(|db| -> Result<()> {
-    let (sql, params) = if sid > 0 {
-      (
-        "UPDATE articles SET unread = ?1 WHERE seed_id = ?2",
-        vec![0, sid],
-      )
-    } else {
-      ("UPDATE articles SET unread = ?1", vec![0])
-    };
+  Ok(())
+}
+
+/// 累加静音规则的静音数量
+pub fn add_muted_count(db: &Connection, rule_id: i64, count: usize) -> Result<()> {
+  db.execute(
+    "UPDATE mute_rules SET muted_count = muted_count + ?2 WHERE id = ?1",
+    params![rule_id, count],
+  )?;
+  Ok(())
+}
+
+/// 取消文章的静音，重新显示。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_unmute_article(app_handle: AppHandle, item_id: i64) -> error::Result<()> {
+  info!("Unmute: {item_id}");
 
-    let mut stmt = db.prepare(sql)?;
-    stmt.execute(params_from_iter(params))?;
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      db.execute(
+        "UPDATE articles SET muted_by = NULL WHERE id = ?1",
+        [item_id],
+      )?;
 
-    // 上报种子未读数量事件
-    app_handle
-      .emit_all(
+      // 隐藏时保留了未读状态，重新显示后未读数量可能变化
+      let article = get_article(db, item_id)?;
+      handle.emit_event(
         "app://seed/unread",
         SeedUnreadCountEvent {
-          id: seed_id,
-          unread_count: 0,
+          id: Some(article.seed_id),
+          unread_count: get_unread_count(db, Some(article.seed_id))?,
         },
-      )
-      .unwrap();
-
-    app_handle
-      .emit_all(
+      );
+      handle.emit_event(
         "app://seed/unread",
         SeedUnreadCountEvent {
           id: None,
-          unread_count: if sid > 0 {
-            get_unread_count(db, None)?
-          } else {
-            0
-          },
+          unread_count: get_unread_count(db, None)?,
         },
-      )
-      .unwrap();
+      );
 
-    app_handle
-      .emit_all(
-        "app://article/unread",
-        ArticleReadEvent {
-          id: -1,
-          unread: false,
-        },
-      )
-      .unwrap();
+      Ok(())
+    })
+    .await?;
 
-    Ok(())
-  });
+  app_handle.emit_event("app://mute/change", ());
 
-  result.unwrap();
-  true
+  Ok(())
 }
 
-fn get_watch_list(db: &Connection) -> Result<Vec<String>> {
-  let mut stmt = db.prepare("SELECT keyword FROM watch_list")?;
+/// 将行转换为 Rule
+fn to_rule(row: &Row) -> Result<Rule> {
+  let condition: String = row.get("condition")?;
+  let actions: String = row.get("actions")?;
+
+  Ok(Rule {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    enabled: row.get("enabled")?,
+    condition: serde_json::from_str(&condition).map_err(|e| {
+      rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+    })?,
+    actions: serde_json::from_str(&actions).unwrap_or_default(),
+  })
+}
+
+/// 获取所有规则，按创建顺序排列
+pub fn get_rules(db: &Connection) -> Result<Vec<Rule>> {
+  let mut stmt = db.prepare("SELECT * FROM rules ORDER BY id")?;
   let mut rows = stmt.query([])?;
   let mut items = Vec::new();
 
   while let Some(row) = rows.next()? {
-    let keyword: String = row.get(0)?;
-    items.push(keyword);
+    items.push(to_rule(row)?);
   }
 
   Ok(items)
 }
 
-/// 获取监视关键字列表。
+fn get_rule(db: &Connection, id: i64) -> Result<Rule> {
+  db.query_row("SELECT * FROM rules WHERE id = ?1", [id], to_rule)
+}
+
+/// 获取规则列表。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_get_watch_list(app_handle: AppHandle) -> Vec<String> {
-  let result = app_handle.db(get_watch_list);
+pub async fn db_get_rules(app_handle: AppHandle) -> error::Result<Vec<Rule>> {
+  let result = app_handle.db(get_rules).await;
+
+  Ok(result?)
+}
+
+/// 插入或更新规则，`id` 为 0 时插入
+fn save_rule(db: &Connection, rule: &Rule) -> error::Result<()> {
+  if rule.name.is_empty() || rule.actions.is_empty() || !rule.condition.is_valid() {
+    return Err(Error::validation("The rule is invalid."));
+  }
 
-  result.unwrap()
+  let empty_tag = rule
+    .actions
+    .iter()
+    .any(|action| matches!(action, RuleAction::Tag { tag } if tag.trim().is_empty()));
+
+  if empty_tag {
+    return Err(Error::validation("The tag of the rule is empty."));
+  }
+
+  let condition = to_json(&rule.condition)?;
+  let actions = to_json(&rule.actions)?;
+  let params = params![rule.name, rule.enabled, condition, actions, rule.id];
+
+  if rule.id > 0 {
+    db.execute(
+      "UPDATE rules SET name = ?1, enabled = ?2, condition = ?3, actions = ?4 WHERE id = ?5",
+      params,
+    )?;
+  } else {
+    db.execute(
+      "INSERT INTO rules (name, enabled, condition, actions) VALUES (?1, ?2, ?3, ?4)",
+      &params[..4],
+    )?;
+  }
 
-  // if let Ok(result) = result {
-  //   result
-  // } else {
-  //   vec![]
-  // }
+  Ok(())
 }
 
-/// 添加监视关键字。
+/// 添加或修改规则，`id` 为 0 时添加。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_add_watch_keyword(app_handle: AppHandle, keyword: String) -> bool {
-  let result = app_handle.db(|db| -> Result<()> {
-    let mut stmt = db.prepare("INSERT INTO watch_list (keyword) VALUES (?1)")?;
-    stmt.execute([keyword])?;
-    Ok(())
-  });
+pub async fn db_save_rule(app_handle: AppHandle, rule: Rule) -> error::Result<()> {
+  app_handle.db_mut(move |db| save_rule(db, &rule)).await?;
+
+  app_handle.emit_event("app://rules/change", ());
 
-  result.unwrap();
+  Ok(())
+}
 
-  app_handle.emit_all("app://watchlist/change", ()).unwrap();
+/// 删除规则。
+#[tauri::command]
+#[specta::specta]
+pub async fn db_delete_rule(app_handle: AppHandle, rule_id: i64) -> error::Result<()> {
+  app_handle
+    .db_mut(move |db| db.execute("DELETE FROM rules WHERE id = ?1", [rule_id]))
+    .await?;
 
-  true
+  app_handle.emit_event("app://rules/change", ());
 
-  // result.is_ok()
+  Ok(())
 }
 
-/// 删除监视关键字。
+/// 对已有文章应用规则，按顺序执行各个动作。
+///
+/// 不插入和通知动作只对新文章有效，此处忽略，已有的文章不会被删除。
+fn apply_rule(db: &Connection, rule: &Rule) -> Result<()> {
+  let condition = matcher_condition(&rule.condition);
+  let params = params_from_iter(condition.params.iter());
+
+  for action in &rule.actions {
+    match action {
+      RuleAction::MarkRead => {
+        let sql = format!(
+          "UPDATE articles SET unread = 0 WHERE unread = 1 AND {}",
+          condition.sql
+        );
+        db.execute(&sql, params.clone())?;
+      }
+      RuleAction::Star => {
+        let sql = format!(
+          "UPDATE articles SET starred = 1 WHERE starred = 0 AND {}",
+          condition.sql
+        );
+        db.execute(&sql, params.clone())?;
+      }
+      RuleAction::Tag { tag } => {
+        db.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+        let sql = format!(
+          "INSERT OR IGNORE INTO article_tags (article_id, tag_id) SELECT articles.id, (SELECT id FROM tags WHERE name = ?) FROM articles WHERE {}",
+          condition.sql
+        );
+        let tag = Value::Text(tag.clone());
+        db.execute(
+          &sql,
+          params_from_iter(std::iter::once(&tag).chain(condition.params.iter())),
+        )?;
+      }
+      RuleAction::Skip | RuleAction::Notify => {}
+    }
+  }
+
+  Ok(())
+}
+
+/// 对已有文章应用规则，禁用的规则不能应用。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_delete_watch_keyword(app_handle: AppHandle, keyword: String) -> bool {
-  let result = app_handle.db(|db| -> Result<()> {
-    let mut stmt = db.prepare("DELETE FROM watch_list WHERE keyword = ?1")?;
-    stmt.execute([keyword])?;
-    Ok(())
-  });
+pub async fn db_apply_rule(app_handle: AppHandle, rule_id: i64) -> error::Result<()> {
+  info!("Apply rule: {rule_id}");
 
-  result.unwrap();
+  let rule = app_handle.db(move |db| get_rule(db, rule_id)).await?;
+
+  if !rule.enabled {
+    return Err(Error::validation("The rule is disabled."));
+  }
+
+  let handle = app_handle.clone();
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let tx = db.transaction()?;
+      apply_rule(&tx, &rule)?;
+      tx.commit()?;
+
+      // 上报所有种子的未读数量，并刷新文章列表
+      for seed in get_all_seeds(db)? {
+        handle.emit_event(
+          "app://seed/unread",
+          SeedUnreadCountEvent {
+            id: Some(seed.id),
+            unread_count: get_unread_count(db, Some(seed.id))?,
+          },
+        );
+      }
 
-  app_handle.emit_all("app://watchlist/change", ()).unwrap();
+      handle.emit_event(
+        "app://seed/unread",
+        SeedUnreadCountEvent {
+          id: None,
+          unread_count: get_unread_count(db, None)?,
+        },
+      );
+      handle.emit_event(
+        "app://article/unread",
+        ArticleReadEvent {
+          id: -1,
+          unread: false,
+        },
+      );
+      handle.emit_event("app://tag/change", ());
 
-  true
+      Ok(())
+    })
+    .await?;
 
-  // result.is_ok()
+  Ok(())
 }
 
 /// 获取设置。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_get_setting(app_handle: AppHandle, key: String) -> String {
-  let result = app_handle.db(|db| -> Result<String> {
-    let mut stmt = db.prepare("SELECT value FROM settings WHERE key = ?1")?;
-    let mut rows = stmt.query([key])?;
-
-    if let Some(row) = rows.next()? {
-      let value: String = row.get("value")?;
-      Ok(value)
-    } else {
-      Ok(String::default())
-    }
-  });
+pub async fn db_get_setting(app_handle: AppHandle, key: String) -> error::Result<String> {
+  let result = app_handle
+    .db(move |db| -> Result<String> {
+      let mut stmt = db.prepare("SELECT value FROM settings WHERE key = ?1")?;
+      let mut rows = stmt.query([key])?;
+
+      if let Some(row) = rows.next()? {
+        let value: String = row.get("value")?;
+        Ok(value)
+      } else {
+        Ok(String::default())
+      }
+    })
+    .await;
 
-  if let Ok(value) = result {
-    value
-  } else {
-    String::default()
-  }
+  Ok(result?)
 }
 
 /// 修改设置。
 #[tauri::command]
 #[specta::specta]
-pub async fn db_set_setting(app_handle: AppHandle, key: String, value: String) -> bool {
-  let result = app_handle.db(|db| -> Result<()> {
-    let mut stmt = db.prepare("REPLACE INTO settings (key, value) VALUES (?1, ?2)")?;
-    stmt.execute([key, value])?;
-    Ok(())
-  });
+pub async fn db_set_setting(
+  app_handle: AppHandle,
+  key: String,
+  value: String,
+) -> error::Result<()> {
+  let result = app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare("REPLACE INTO settings (key, value) VALUES (?1, ?2)")?;
+      stmt.execute([key, value])?;
+      Ok(())
+    })
+    .await;
 
-  result.is_ok()
+  Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// 最新版本的内存数据库
+  fn open() -> Connection {
+    let mut db = Connection::open_in_memory().unwrap();
+    db.pragma_update(None, "foreign_keys", "ON").unwrap();
+    register_functions(&db).unwrap();
+    upgrade_if_needed(&mut db, 0).unwrap();
+    db.execute(
+      "INSERT INTO seeds (id, name, url) VALUES (1, 'Seed', 'https://example.com/feed')",
+      [],
+    )
+    .unwrap();
+    db
+  }
+
+  fn matcher(keyword: &str, match_mode: MatchMode) -> Matcher {
+    Matcher {
+      keyword: keyword.to_string(),
+      match_mode,
+      case_sensitive: false,
+      fields: vec![MatchField::Title],
+      seed_id: None,
+      folder_id: None,
+    }
+  }
+
+  /// 添加文章，返回 ID
+  fn add_article(db: &Connection, title: &str, author: &str, pub_date: i64) -> i64 {
+    db.execute(
+      "INSERT INTO articles (seed_id, guid, title, author, desc, link, pub_date, unread) VALUES (1, ?1, ?1, ?2, ?1, ?1, ?3, 1)",
+      params![title, author, pub_date],
+    )
+    .unwrap();
+    db.last_insert_rowid()
+  }
+
+  fn article_ids(result: ArticleResult) -> Vec<i64> {
+    result.articles.iter().map(|article| article.id).collect()
+  }
+
+  #[test]
+  fn search_with_filters() {
+    let db = open();
+    let first = add_article(&db, "Rust release", "Alice", 100);
+    add_article(&db, "Rust release notes", "Bob", 200);
+    add_article(&db, "Go release", "Alice", 300);
+    db.execute("UPDATE articles SET starred = 1", []).unwrap();
+
+    let filters = ArticleFilters {
+      search: Some(String::from("rust")),
+      author: Some(String::from("alice")),
+      read_state: Some(ReadState::Unread),
+      starred: Some(true),
+      since: Some(50),
+      until: Some(250),
+      ..Default::default()
+    };
+    let result = get_articles(&db, &filters).unwrap();
+    assert_eq!(article_ids(result), vec![first]);
+
+    let filters = ArticleFilters {
+      search: Some(String::from("release")),
+      muted: Some(true),
+      ..Default::default()
+    };
+    assert!(get_articles(&db, &filters).unwrap().articles.is_empty());
+  }
+
+  #[test]
+  fn hidden_articles_keep_unread() {
+    let db = open();
+    let hidden = add_article(&db, "Rust release", "Alice", 100);
+    add_article(&db, "Go release", "Alice", 200);
+    db.execute(
+      "INSERT INTO mute_rules (id, keyword) VALUES (1, 'Rust')",
+      [],
+    )
+    .unwrap();
+    db.execute("UPDATE articles SET muted_by = 1 WHERE id = ?1", [hidden])
+      .unwrap();
+    assert_eq!(get_unread_count(&db, Some(1)).unwrap(), 1);
+    assert_eq!(get_unread_count(&db, None).unwrap(), 1);
+
+    // 删除规则后重新显示，未读状态不变
+    db.execute("DELETE FROM mute_rules WHERE id = 1", [])
+      .unwrap();
+    assert_eq!(get_unread_count(&db, None).unwrap(), 2);
+    assert!(get_article(&db, hidden).unwrap().unread);
+  }
+
+  #[test]
+  fn search_virtual_seed_pages() {
+    let db = open();
+    let first = add_article(&db, "Rust 1.0", "Alice", 100);
+    let second = add_article(&db, "Rust 2.0", "Alice", 200);
+    add_article(&db, "Rust 3.0", "Alice", 300);
+    db.execute(
+      "UPDATE articles SET starred = 1 WHERE id IN (?1, ?2)",
+      [first, second],
+    )
+    .unwrap();
+
+    let mut filters = ArticleFilters {
+      search: Some(String::from("rust")),
+      limit: Some(1),
+      ..Default::default()
+    };
+    let mut ids = Vec::new();
+
+    loop {
+      let result = get_virtual_articles(&db, VirtualSeed::Starred, &filters).unwrap();
+      assert!(result
+        .articles
+        .iter()
+        .all(|article| article.snippet.is_some()));
+      filters.cursor = result.next_cursor.clone();
+      ids.extend(article_ids(result));
+
+      if filters.cursor.is_none() {
+        break;
+      }
+    }
+
+    assert_eq!(ids, vec![second, first]);
+  }
+
+  #[test]
+  fn search_watch_list() {
+    let db = open();
+    let watched = add_article(&db, "Rust release", "Alice", 100);
+    add_article(&db, "Go release", "Alice", 200);
+
+    for seed_id in [None, Some(1)] {
+      let mut rule = WatchRule {
+        id: 0,
+        matcher: matcher("rust", MatchMode::WholeWord),
+      };
+      rule.matcher.seed_id = seed_id;
+      save_watch_rule(&db, &rule).unwrap();
+    }
+
+    let filters = ArticleFilters {
+      search: Some(String::from("release")),
+      ..Default::default()
+    };
+    let result = get_virtual_articles(&db, VirtualSeed::WatchList, &filters).unwrap();
+    assert_eq!(article_ids(result), vec![watched]);
+    assert_eq!(
+      get_virtual_unread_count(&db, VirtualSeed::WatchList).unwrap(),
+      1
+    );
+  }
+
+  #[test]
+  fn apply_rule_keeps_existing_articles() {
+    let db = open();
+    let id = add_article(&db, "Sponsored post", "Alice", 100);
+    let rule = Rule {
+      id: 0,
+      name: String::from("Ads"),
+      enabled: true,
+      condition: matcher("sponsored", MatchMode::Substring),
+      actions: vec![RuleAction::Skip, RuleAction::MarkRead],
+    };
+    apply_rule(&db, &rule).unwrap();
+
+    let article = get_article(&db, id).unwrap();
+    assert!(!article.unread);
+  }
+
+  #[test]
+  fn missing_article_is_not_found() {
+    let db = open();
+    let err = Error::from(get_article(&db, 42).unwrap_err());
+    assert!(matches!(err, Error::NotFound(_)), "{:?}", err);
+  }
+
+  #[test]
+  fn missing_seed_is_not_found() {
+    let db = open();
+    let err = Error::from(
+      update_seed(
+        &db,
+        "UPDATE seeds SET paused = ?1 WHERE id = ?2",
+        params![true, 42],
+      )
+      .unwrap_err(),
+    );
+    assert!(matches!(err, Error::NotFound(_)), "{:?}", err);
+
+    update_seed(
+      &db,
+      "UPDATE seeds SET paused = ?1 WHERE id = ?2",
+      params![true, 1],
+    )
+    .unwrap();
+  }
+
+  #[test]
+  fn missing_rule_is_not_found() {
+    let db = open();
+    let err = Error::from(get_rule(&db, 42).unwrap_err());
+    assert!(matches!(err, Error::NotFound(_)), "{:?}", err);
+  }
+
+  #[test]
+  fn constraint_violation_is_validation() {
+    let db = open();
+    let err = Error::from(
+      db.execute(
+        "INSERT INTO seeds (name, url) VALUES ('Other', 'https://example.com/feed')",
+        [],
+      )
+      .unwrap_err(),
+    );
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+  }
+
+  #[test]
+  fn bad_regex_is_validation() {
+    let db = open();
+    let watch = WatchRule {
+      id: 0,
+      matcher: matcher("(", MatchMode::Regex),
+    };
+    let err = save_watch_rule(&db, &watch).unwrap_err();
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+
+    let rule = Rule {
+      id: 0,
+      name: String::from("Rule"),
+      enabled: true,
+      condition: matcher("[a-", MatchMode::Regex),
+      actions: vec![RuleAction::MarkRead],
+    };
+    let err = save_rule(&db, &rule).unwrap_err();
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+
+    let rule = Rule {
+      condition: matcher("rust", MatchMode::WholeWord),
+      actions: vec![RuleAction::Tag {
+        tag: String::from("  "),
+      }],
+      ..rule
+    };
+    let err = save_rule(&db, &rule).unwrap_err();
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+
+    let count: i64 = db
+      .query_row("SELECT COUNT(*) FROM watch_list", [], |row| row.get(0))
+      .unwrap();
+    assert_eq!(count, 0);
+
+    save_watch_rule(
+      &db,
+      &WatchRule {
+        id: 0,
+        matcher: matcher("rust", MatchMode::WholeWord),
+      },
+    )
+    .unwrap();
+  }
+
+  #[test]
+  fn database_error_hides_details() {
+    let db = open();
+    let err = Error::from(db.execute("SELECT * FROM no_such_table", []).unwrap_err());
+    assert!(
+      matches!(&err, Error::Database(message) if message == "A database error occurred."),
+      "{:?}",
+      err
+    );
+  }
+
+  #[test]
+  fn unavailable_database() {
+    let result: error::Result<()> = DbResult::unavailable();
+    assert!(
+      matches!(&result, Err(Error::Database(message)) if message == "The database is not available."),
+      "{:?}",
+      result
+    );
+
+    let result: Result<()> = DbResult::unavailable();
+    let err = result.unwrap_err();
+    assert_eq!(
+      err.sqlite_error_code(),
+      Some(rusqlite::ErrorCode::CannotOpen)
+    );
+  }
 }
@@ -0,0 +1,289 @@
+use std::{
+  env,
+  net::{IpAddr, Ipv4Addr, SocketAddr},
+  sync::Arc,
+  time::Duration,
+};
+
+use anyhow::{bail, Result};
+use reqwest::{
+  dns::{Addrs, Name, Resolve, Resolving},
+  header::{HeaderMap, CONTENT_TYPE},
+  redirect::Policy,
+  Client, Response, Url,
+};
+use tauri::AppHandle;
+use url::Host;
+
+use crate::{
+  db::DbAccess,
+  error::{self, Error},
+  job::{client_builder, get_generic_settings, get_proxy, ProxySettings},
+};
+
+/// 下载时最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 10;
+/// 系统代理的环境变量
+const SYSTEM_PROXY_VARS: &[&str] = &[
+  "HTTP_PROXY",
+  "http_proxy",
+  "HTTPS_PROXY",
+  "https_proxy",
+  "ALL_PROXY",
+  "all_proxy",
+];
+
+/// 是否为本机或内网 IP 地址
+fn is_internal_ip(ip: IpAddr) -> bool {
+  fn is_internal_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    ip.is_private()
+      || ip.is_loopback()
+      || ip.is_link_local()
+      || ip.is_unspecified()
+      || ip.is_broadcast()
+      // 运营商级 NAT
+      || (a == 100 && (b & 0xc0) == 64)
+  }
+
+  match ip {
+    IpAddr::V4(ip) => is_internal_ipv4(ip),
+    IpAddr::V6(ip) => {
+      let first = ip.segments()[0];
+
+      ip.is_loopback()
+        || ip.is_unspecified()
+        // 唯一本地地址
+        || (first & 0xfe00) == 0xfc00
+        // 链路本地地址
+        || (first & 0xffc0) == 0xfe80
+        || ip.to_ipv4_mapped().is_some_and(is_internal_ipv4)
+    }
+  }
+}
+
+/// 是否为本机或内网地址，包括 `localhost`、`.local` 和没有点的主机名
+fn is_internal_host(host: Host<&str>) -> bool {
+  match host {
+    Host::Domain(domain) => {
+      let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+
+      domain == "localhost"
+        || domain.ends_with(".localhost")
+        || domain.ends_with(".local")
+        || domain.ends_with(".internal")
+        || !domain.contains('.')
+    }
+    Host::Ipv4(ip) => is_internal_ip(IpAddr::V4(ip)),
+    Host::Ipv6(ip) => is_internal_ip(IpAddr::V6(ip)),
+  }
+}
+
+/// 只返回公网地址的 DNS 解析器，防止公网域名解析到本机或内网地址。
+///
+/// 每次建立连接都会经过解析器，重定向后的地址同样受到限制。代理服务器本身的地址不受限制；
+/// 使用代理时目标域名由代理解析，只能检查 URL 中的主机名。
+struct PublicResolver {
+  /// 代理服务器的主机名
+  proxy_hosts: Vec<String>,
+}
+
+impl Resolve for PublicResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    let host = name.as_str().to_string();
+    let is_proxy = self
+      .proxy_hosts
+      .iter()
+      .any(|proxy_host| proxy_host.eq_ignore_ascii_case(&host));
+
+    Box::pin(async move {
+      let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+        .await?
+        .filter(|addr| is_proxy || !is_internal_ip(addr.ip()))
+        .collect();
+
+      if addrs.is_empty() {
+        return Err(format!("{} resolves to a forbidden address.", host).into());
+      }
+
+      let addrs: Addrs = Box::new(addrs.into_iter());
+      Ok(addrs)
+    })
+  }
+}
+
+/// 代理服务器的主机名。使用系统代理时从环境变量中读取
+fn proxy_hosts(proxy: &ProxySettings) -> Vec<String> {
+  match proxy.t.as_str() {
+    "http" => vec![proxy.host.clone()],
+    "sys" => SYSTEM_PROXY_VARS
+      .iter()
+      .filter_map(|name| env::var(name).ok())
+      .filter_map(|value| {
+        let value = value.trim();
+
+        if value.contains("://") {
+          Url::parse(value).ok()
+        } else {
+          Url::parse(&format!("http://{}", value)).ok()
+        }
+      })
+      .filter_map(|url| url.host_str().map(String::from))
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+/// 是否允许下载该 URL：只允许 HTTP(S)，默认不允许本机和内网地址
+fn is_allowed_url(url: &Url, allow_private_network: bool) -> bool {
+  matches!(url.scheme(), "http" | "https")
+    && url
+      .host()
+      .is_some_and(|host| allow_private_network || !is_internal_host(host))
+}
+
+/// 下载图片等资源的客户端，网页传来的 URL 都通过它下载
+pub struct Downloader {
+  /// HTTP 客户端，重定向到不允许的地址时失败
+  pub client: Client,
+  /// 图片缓存大小上限，字节
+  pub cache_quota: u64,
+  /// 是否允许本机和内网地址
+  allow_private_network: bool,
+}
+
+impl Downloader {
+  /// 按代理设置和一般设置创建下载客户端
+  pub async fn new(app_handle: &AppHandle) -> Result<Self> {
+    let (proxy, generic) = app_handle
+      .db(|db| -> Result<_> { Ok((get_proxy(db)?, get_generic_settings(db)?)) })
+      .await?;
+    let allow_private_network = generic.allow_private_network;
+    let redirect = Policy::custom(move |attempt| {
+      if attempt.previous().len() >= MAX_REDIRECTS {
+        attempt.error("Too many redirects.")
+      } else if is_allowed_url(attempt.url(), allow_private_network) {
+        attempt.follow()
+      } else {
+        attempt.error("Redirected to a forbidden address.")
+      }
+    });
+    let mut client = client_builder(&proxy)?
+      .timeout(Duration::from_secs(generic.timeout.into()))
+      .redirect(redirect);
+
+    if !allow_private_network {
+      let proxy_hosts = proxy_hosts(&proxy);
+      client = client.dns_resolver(Arc::new(PublicResolver { proxy_hosts }));
+    }
+
+    let client = client.build()?;
+
+    Ok(Self {
+      client,
+      cache_quota: generic.cache_quota(),
+      allow_private_network,
+    })
+  }
+
+  /// 检查是否允许下载该 URL
+  pub fn check_url(&self, url: &Url) -> error::Result<()> {
+    if is_allowed_url(url, self.allow_private_network) {
+      Ok(())
+    } else {
+      Err(Error::validation(format!(
+        "Downloading {} is not allowed.",
+        url
+      )))
+    }
+  }
+
+  /// 下载图片，最多 `limit` 字节，返回 MIME 类型和数据。`headers` 为额外的请求头。
+  ///
+  /// 服务器返回的类型常常不准确，优先使用文件头判断的类型；无法判断时只接受 `image/*`。
+  pub async fn get_image(
+    &self,
+    url: &Url,
+    headers: HeaderMap,
+    limit: usize,
+  ) -> Result<(String, Vec<u8>)> {
+    self.check_url(url)?;
+
+    let response = self
+      .client
+      .get(url.clone())
+      .headers(headers)
+      .send()
+      .await?
+      .error_for_status()?;
+    let declared = content_type(&response);
+    let (data, truncated) = read_limited(response, limit).await?;
+
+    if truncated {
+      bail!("{} is too large.", url);
+    }
+
+    let content_type = match (sniff(&data), declared) {
+      (Some(sniffed), _) => sniffed.to_string(),
+      (None, Some(declared)) if declared.starts_with("image/") => declared,
+      _ => bail!("{} is not an image.", url),
+    };
+
+    Ok((content_type, data))
+  }
+}
+
+/// 响应声明的 MIME 类型，不含参数，小写
+fn content_type(response: &Response) -> Option<String> {
+  response
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.split(';').next())
+    .map(|value| value.trim().to_ascii_lowercase())
+}
+
+/// 读取响应内容，最多读取 `limit` 字节，返回内容及是否超出了上限
+pub async fn read_limited(mut response: Response, limit: usize) -> Result<(Vec<u8>, bool)> {
+  if response
+    .content_length()
+    .is_some_and(|length| length > limit as u64)
+  {
+    return Ok((Vec::new(), true));
+  }
+
+  let mut data = Vec::new();
+
+  while let Some(chunk) = response.chunk().await? {
+    data.extend_from_slice(&chunk);
+
+    if data.len() > limit {
+      data.truncate(limit);
+      return Ok((data, true));
+    }
+  }
+
+  Ok((data, false))
+}
+
+/// 根据文件头判断图片类型
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+  if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+    Some("image/png")
+  } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+    Some("image/gif")
+  } else if data.starts_with(b"\xff\xd8\xff") {
+    Some("image/jpeg")
+  } else if data.starts_with(b"\x00\x00\x01\x00") {
+    Some("image/x-icon")
+  } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+    Some("image/webp")
+  } else if data.len() >= 12 && &data[4..12] == b"ftypavif" {
+    Some("image/avif")
+  } else if data.starts_with(b"BM") {
+    Some("image/bmp")
+  } else {
+    None
+  }
+}
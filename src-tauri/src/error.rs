@@ -1,64 +1,148 @@
+use log::error;
 use serde::Serialize;
+use specta::Type;
 
-#[derive(Debug)]
-pub struct Error(pub anyhow::Error);
+/// 返回给前端的错误，序列化为 `{ code, message }`。
+///
+/// `code` 是稳定的错误代码，前端据此区分错误；`message` 可以直接显示给用户。
+#[derive(Debug, Serialize, Type)]
+#[serde(rename_all = "camelCase", tag = "code", content = "message")]
+pub enum Error {
+  /// 网络请求失败
+  Network(String),
+  /// 数据格式错误，如无法解析的订阅源或设置
+  Parse(String),
+  /// 数据库错误
+  Database(String),
+  /// 要操作的对象不存在
+  NotFound(String),
+  /// 参数无效
+  Validation(String),
+  /// 操作正在进行中，稍后再试
+  Busy(String),
+  /// 其他错误
+  Internal(String),
+}
+
+impl Error {
+  /// 参数无效
+  pub fn validation(message: impl Into<String>) -> Self {
+    Self::Validation(message.into())
+  }
+
+  /// 操作正在进行中
+  pub fn busy(message: impl Into<String>) -> Self {
+    Self::Busy(message.into())
+  }
+
+  /// 对象不存在
+  pub fn not_found(message: impl Into<String>) -> Self {
+    Self::NotFound(message.into())
+  }
+}
 
 impl std::fmt::Display for Error {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    #[cfg(debug_assertions)]
-    {
-      write!(f, "{:#}", self.0)
-    }
-
-    #[cfg(not(debug_assertions))]
-    {
-      write!(f, "{}", self.0)
+    match self {
+      Self::Network(message)
+      | Self::Parse(message)
+      | Self::Database(message)
+      | Self::NotFound(message)
+      | Self::Validation(message)
+      | Self::Busy(message)
+      | Self::Internal(message) => write!(f, "{}", message),
     }
   }
 }
 
 impl std::error::Error for Error {}
 
-impl Serialize for Error {
-  fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
-  where
-    S: serde::Serializer,
-  {
-    #[cfg(debug_assertions)]
-    {
-      serializer.serialize_str(&format!("{:#}", self.0))
+/// SQLite 的错误信息含有 SQL 语句和内部细节，只写入日志，返回给前端的是固定的信息
+impl From<rusqlite::Error> for Error {
+  fn from(value: rusqlite::Error) -> Self {
+    match value {
+      rusqlite::Error::QueryReturnedNoRows => Self::not_found("The item does not exist."),
+      rusqlite::Error::SqliteFailure(err, _)
+        if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+      {
+        Self::validation("The item conflicts with an existing one.")
+      }
+      rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::CannotOpen => {
+        Self::Database("The database is not available.".to_string())
+      }
+      _ => {
+        error!("Database error: {:?}", value);
+        Self::Database("A database error occurred.".to_string())
+      }
     }
+  }
+}
 
-    #[cfg(not(debug_assertions))]
-    {
-      serializer.serialize_str("errors disabled in production.")
+impl From<reqwest::Error> for Error {
+  fn from(value: reqwest::Error) -> Self {
+    if value.is_decode() {
+      Self::Parse(value.to_string())
+    } else {
+      Self::Network(value.to_string())
     }
   }
 }
 
-impl From<anyhow::Error> for Error {
-  fn from(value: anyhow::Error) -> Self {
-    Self(value)
+impl From<reqwest::header::ToStrError> for Error {
+  fn from(value: reqwest::header::ToStrError) -> Self {
+    Self::Parse(value.to_string())
   }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+impl From<rss::Error> for Error {
+  fn from(value: rss::Error) -> Self {
+    Self::Parse(value.to_string())
+  }
+}
 
-pub trait IntoResult<T> {
-  fn into_result(self) -> Result<T>;
+impl From<chrono::ParseError> for Error {
+  fn from(value: chrono::ParseError) -> Self {
+    Self::Parse(value.to_string())
+  }
 }
 
-impl<T, E> IntoResult<T> for std::result::Result<T, E>
-where
-  E: Into<anyhow::Error>,
-{
-  fn into_result(self) -> Result<T> {
-    self.map_err(|e| Error(e.into()))
+impl From<serde_json::Error> for Error {
+  fn from(value: serde_json::Error) -> Self {
+    Self::Parse(value.to_string())
   }
 }
 
-impl<T> IntoResult<T> for anyhow::Error {
-  fn into_result(self) -> Result<T> {
-    Err(Error(self))
+/// 已知类型的错误按类型转换，其他错误的信息可能含有路径等内部细节，只写入日志
+impl From<anyhow::Error> for Error {
+  fn from(value: anyhow::Error) -> Self {
+    let value = match value.downcast::<Error>() {
+      Ok(err) => return err,
+      Err(value) => value,
+    };
+    let value = match value.downcast::<rusqlite::Error>() {
+      Ok(err) => return err.into(),
+      Err(value) => value,
+    };
+    let value = match value.downcast::<reqwest::Error>() {
+      Ok(err) => return err.into(),
+      Err(value) => value,
+    };
+    let value = match value.downcast::<rss::Error>() {
+      Ok(err) => return err.into(),
+      Err(value) => value,
+    };
+    let value = match value.downcast::<chrono::ParseError>() {
+      Ok(err) => return err.into(),
+      Err(value) => value,
+    };
+    let value = match value.downcast::<serde_json::Error>() {
+      Ok(err) => return err.into(),
+      Err(value) => value,
+    };
+
+    error!("Internal error: {:#}", value);
+    Self::Internal("An internal error occurred.".to_string())
   }
 }
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,5 +1,22 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use tauri::{AppHandle, Manager};
+
+use crate::seed::FetchStatus;
+
+/// 向所有窗口发送事件。事件只用于通知界面刷新，发送失败时记录日志，不影响已完成的操作。
+pub trait EmitEvent {
+  fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S);
+}
+
+impl EmitEvent for AppHandle {
+  fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) {
+    if let Err(err) = self.emit_all(event, payload) {
+      warn!("Failed to emit {}: {:?}", event, err);
+    }
+  }
+}
 
 /// 文章未读事件
 #[derive(Debug, Clone, Deserialize, Serialize, Type)]
@@ -10,6 +27,24 @@ pub struct ArticleReadEvent {
   pub unread: bool,
 }
 
+/// 文章星标事件
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct ArticleStarEvent {
+  /// 文章 ID
+  pub id: i64,
+  /// 是否加了星标
+  pub starred: bool,
+}
+
+/// 文章标签事件
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct ArticleTagEvent {
+  /// 文章 ID
+  pub id: i64,
+  /// 文章当前的标签
+  pub tags: Vec<String>,
+}
+
 /// 种子未读数量事件
 #[derive(Debug, Clone, Deserialize, Serialize, Type)]
 #[serde(rename_all = "camelCase")]
@@ -19,3 +54,44 @@ pub struct SeedUnreadCountEvent {
   /// 未读数量
   pub unread_count: i32,
 }
+
+/// 抓取开始事件
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchStartEvent {
+  /// 要抓取的种子 ID
+  pub seed_ids: Vec<i64>,
+}
+
+/// 抓取结束事件
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchFinishEvent {
+  /// 成功的种子数量
+  pub ok: u32,
+  /// 没有变化的种子数量
+  pub not_modified: u32,
+  /// 失败的种子数量
+  pub failed: u32,
+  /// 网络不可用而跳过的种子数量
+  pub offline: u32,
+}
+
+impl FetchFinishEvent {
+  /// 按种子的抓取状态计数
+  pub fn count(&mut self, status: FetchStatus) {
+    match status {
+      FetchStatus::Ok => self.ok += 1,
+      FetchStatus::NotModified => self.not_modified += 1,
+      FetchStatus::Error => self.failed += 1,
+      FetchStatus::Offline => self.offline += 1,
+    }
+  }
+}
+
+/// 图片缓存完成事件
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+pub struct MediaCachedEvent {
+  /// 图片地址
+  pub url: String,
+}
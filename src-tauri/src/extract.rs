@@ -0,0 +1,196 @@
+use anyhow::{anyhow, Result};
+use kuchikiki::{parse_html, traits::TendrilSink, NodeRef};
+use regex::Regex;
+use reqwest::Url;
+
+use crate::sanitize::{inner_html, sanitize_node};
+
+/// 提取之前删除的元素，这些元素不会包含正文
+const UNLIKELY_SELECTOR: &str =
+  "script, style, noscript, iframe, form, nav, header, footer, aside, button, select, textarea";
+/// 计分的段落
+const PARAGRAPH_SELECTOR: &str = "p, pre, td, blockquote";
+/// 段落的最短长度，更短的段落不计分
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+/// 正文的最短长度，更短时认为提取失败
+const MIN_CONTENT_LENGTH: usize = 140;
+
+/// class 或 id 像是正文的元素
+const POSITIVE_PATTERN: &str = r"(?i)article|body|content|entry|main|page|post|text|blog|story";
+/// class 或 id 像是评论、导航、广告等的元素
+const NEGATIVE_PATTERN: &str = r"(?i)comment|meta|footer|footnote|sidebar|share|social|related|promo|sponsor|\bad\b|ads|nav|menu|widget|hidden";
+
+/// 候选元素及其得分
+struct Candidate {
+  node: NodeRef,
+  score: f64,
+}
+
+/// 元素的文本长度
+fn text_length(node: &NodeRef) -> usize {
+  node.text_contents().trim().chars().count()
+}
+
+/// 元素中链接文本所占的比例
+fn link_density(node: &NodeRef) -> f64 {
+  let length = text_length(node);
+
+  if length == 0 {
+    return 0.0;
+  }
+
+  let link_length: usize = node
+    .select("a")
+    .map(|links| links.map(|link| text_length(link.as_node())).sum())
+    .unwrap_or(0);
+
+  link_length as f64 / length as f64
+}
+
+/// 根据 class 和 id 给元素加减分
+fn class_weight(node: &NodeRef, positive: &Regex, negative: &Regex) -> f64 {
+  let Some(element) = node.as_element() else {
+    return 0.0;
+  };
+  let attributes = element.attributes.borrow();
+  let mut weight = 0.0;
+
+  for name in ["class", "id"] {
+    if let Some(value) = attributes.get(name) {
+      if positive.is_match(value) {
+        weight += 25.0;
+      }
+
+      if negative.is_match(value) {
+        weight -= 25.0;
+      }
+    }
+  }
+
+  weight
+}
+
+/// 给候选元素加分，第一次遇到时按 class 和 id 初始化得分
+fn add_score(
+  candidates: &mut Vec<Candidate>,
+  node: NodeRef,
+  score: f64,
+  positive: &Regex,
+  negative: &Regex,
+) {
+  if let Some(candidate) = candidates
+    .iter_mut()
+    .find(|candidate| candidate.node == node)
+  {
+    candidate.score += score;
+  } else {
+    let weight = class_weight(&node, positive, negative);
+    candidates.push(Candidate {
+      node,
+      score: weight + score,
+    });
+  }
+}
+
+/// 用类似 Readability 的方法查找正文所在的元素：段落按长度和逗号数量计分，分数累加到父元素和祖父元素上，
+/// 再按链接密度降低分数，得分最高的就是正文。
+fn find_content(document: &NodeRef) -> Result<Option<NodeRef>> {
+  let positive = Regex::new(POSITIVE_PATTERN)?;
+  let negative = Regex::new(NEGATIVE_PATTERN)?;
+  let mut candidates = Vec::new();
+  let paragraphs = document
+    .select(PARAGRAPH_SELECTOR)
+    .map_err(|_| anyhow!("Invalid selector."))?;
+
+  for paragraph in paragraphs {
+    let paragraph = paragraph.as_node();
+    let text = paragraph.text_contents();
+    let length = text.trim().chars().count();
+
+    if length < MIN_PARAGRAPH_LENGTH {
+      continue;
+    }
+
+    let commas = text.matches([',', '，']).count();
+    let score = 1.0 + commas as f64 + (length / 100).min(3) as f64;
+
+    if let Some(parent) = paragraph.parent() {
+      if let Some(grandparent) = parent.parent() {
+        add_score(
+          &mut candidates,
+          grandparent,
+          score / 2.0,
+          &positive,
+          &negative,
+        );
+      }
+
+      add_score(&mut candidates, parent, score, &positive, &negative);
+    }
+  }
+
+  let best = candidates
+    .into_iter()
+    .map(|candidate| Candidate {
+      score: candidate.score * (1.0 - link_density(&candidate.node)),
+      node: candidate.node,
+    })
+    .max_by(|a, b| a.score.total_cmp(&b.score));
+
+  Ok(best.map(|candidate| candidate.node))
+}
+
+/// 从网页中提取正文，并清理为安全的 HTML。
+///
+/// 指定了 `selector` 时提取所有匹配的元素，否则自动查找正文。没有找到足够长的正文时返回 `None`。
+pub fn extract_content(html: &str, base: &Url, selector: Option<&str>) -> Result<Option<String>> {
+  let document = parse_html().one(html);
+
+  for node in document
+    .select(UNLIKELY_SELECTOR)
+    .map_err(|_| anyhow!("Invalid selector."))?
+    .collect::<Vec<_>>()
+  {
+    node.as_node().detach();
+  }
+
+  let content = match selector {
+    Some(selector) => {
+      let nodes: Vec<_> = document
+        .select(selector)
+        .map_err(|_| anyhow!("Invalid selector: {}", selector))?
+        .map(|node| node.as_node().clone())
+        .collect();
+
+      if nodes.is_empty() {
+        return Ok(None);
+      }
+
+      // 把匹配的元素移到一个容器中
+      let container = parse_html().one("<div></div>");
+      let container = container
+        .select_first("div")
+        .map_err(|_| anyhow!("Invalid selector."))?
+        .as_node()
+        .clone();
+
+      for node in nodes {
+        container.append(node);
+      }
+
+      container
+    }
+    None => match find_content(&document)? {
+      Some(node) => node,
+      None => return Ok(None),
+    },
+  };
+
+  if text_length(&content) < MIN_CONTENT_LENGTH {
+    return Ok(None);
+  }
+
+  sanitize_node(&content, Some(base));
+
+  Ok(Some(inner_html(&content)))
+}
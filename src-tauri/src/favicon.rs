@@ -0,0 +1,120 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use log::debug;
+use regex::Regex;
+use reqwest::{header::HeaderMap, Url};
+
+use crate::download::{read_limited, Downloader};
+
+/// 图标大小上限，字节
+const MAX_ICON_SIZE: usize = 64 * 1024;
+/// 查找图标时最多读取的网页大小，字节。`<link>` 在 `<head>` 中，不需要读取整个网页
+const MAX_PAGE_SIZE: usize = 256 * 1024;
+/// 图标的更新周期，秒
+pub const REFRESH_INTERVAL: i64 = 7 * 24 * 60 * 60;
+
+/// 下载图标，返回 data URL
+async fn download_icon(downloader: &Downloader, url: &Url) -> Result<String> {
+  let (content_type, data) = downloader
+    .get_image(url, HeaderMap::new(), MAX_ICON_SIZE)
+    .await?;
+
+  Ok(format!(
+    "data:{};base64,{}",
+    content_type,
+    STANDARD.encode(&data)
+  ))
+}
+
+/// 从网页的 `<link rel="icon">` 中查找图标的 URL
+async fn find_icon_links(downloader: &Downloader, site: &Url) -> Result<Vec<Url>> {
+  downloader.check_url(site)?;
+
+  let response = downloader
+    .client
+    .get(site.clone())
+    .send()
+    .await?
+    .error_for_status()?;
+  // 重定向后以最终的地址解析相对 URL
+  let base = response.url().clone();
+  let (data, _) = read_limited(response, MAX_PAGE_SIZE).await?;
+  let html = String::from_utf8_lossy(&data);
+
+  let link = Regex::new(r"(?is)<link\b[^>]*>")?;
+  let attr = Regex::new(r#"(?is)\b(rel|href)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#)?;
+  let mut urls = Vec::new();
+
+  for tag in link.find_iter(&html) {
+    let mut rel = None;
+    let mut href = None;
+
+    for captures in attr.captures_iter(tag.as_str()) {
+      let value = captures
+        .get(2)
+        .or_else(|| captures.get(3))
+        .or_else(|| captures.get(4))
+        .map(|value| value.as_str());
+
+      if captures[1].eq_ignore_ascii_case("rel") {
+        rel = value;
+      } else {
+        href = value;
+      }
+    }
+
+    let is_icon = rel.is_some_and(|rel| {
+      rel
+        .split_ascii_whitespace()
+        .any(|token| token.eq_ignore_ascii_case("icon"))
+    });
+
+    if let (true, Some(href)) = (is_icon, href) {
+      if let Ok(url) = base.join(href.trim()) {
+        urls.push(url);
+      }
+    }
+  }
+
+  Ok(urls)
+}
+
+/// 获取种子的图标，依次尝试订阅源的 `<image>`、网站的 `<link rel="icon">` 和 `/favicon.ico`，返回 data URL。
+/// `link` 和 `image` 为频道的网站链接和图片 URL。都失败时返回 `None`。
+pub async fn fetch_favicon(
+  downloader: &Downloader,
+  link: Option<&str>,
+  image: Option<&str>,
+  feed_url: &str,
+) -> Option<String> {
+  let feed_url = Url::parse(feed_url).ok()?;
+  // 没有网站链接时使用订阅源所在网站的首页
+  let site = link
+    .and_then(|link| feed_url.join(link).ok())
+    .filter(|site| site.scheme().starts_with("http"))
+    .or_else(|| feed_url.join("/").ok())
+    .unwrap_or_else(|| feed_url.clone());
+  let mut candidates = Vec::new();
+
+  if let Some(url) = image.and_then(|image| feed_url.join(image).ok()) {
+    candidates.push(url);
+  }
+
+  match find_icon_links(downloader, &site).await {
+    Ok(urls) => candidates.extend(urls),
+    Err(err) => debug!("Failed to find icons in {}: {:?}", site, err),
+  }
+
+  if let Ok(url) = site.join("/favicon.ico") {
+    candidates.push(url);
+  }
+
+  for url in candidates {
+    match download_icon(downloader, &url).await {
+      Ok(data) => return Some(data),
+      Err(err) => debug!("Failed to download icon {}: {:?}", url, err),
+    }
+  }
+
+  None
+}
@@ -1,43 +1,121 @@
-use anyhow::Result;
+use std::{
+  collections::{HashMap, HashSet},
+  sync::atomic::{AtomicBool, Ordering},
+  time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use chrono::{DateTime, Days, Local};
+use encoding_rs::{Encoding, UTF_8};
 use log::{debug, info, warn};
 use reqwest::{
-  header::{CONTENT_TYPE, REFERER},
-  Proxy,
+  header::{
+    HeaderMap, HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, ETAG, HOST,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, REFERER, TRANSFER_ENCODING, UPGRADE,
+  },
+  Client, ClientBuilder, Proxy, StatusCode, Url,
 };
 use rss::{Channel, Item};
 use rusqlite::{params, Connection};
 use serde::Deserialize;
 use specta::Type;
-use tauri::{AppHandle, Manager};
+use tauri::{async_runtime::spawn_blocking, AppHandle};
 
 use crate::{
-  app_handle::get_app_handle,
-  db::{get_all_seeds, initialize, DbAccess},
-  error::IntoResult,
-  events::SeedUnreadCountEvent,
-  seed::Seed,
+  cache::{add_article_images, get_cached, prefetch, store, MAX_IMAGE_SIZE},
+  db::{
+    add_muted_count, get_all_seeds, get_folder_seed_ids, get_mute_rules, get_rules,
+    get_seed_folder_ids, tag_article, DbAccess,
+  },
+  download::{read_limited, Downloader},
+  error::{self, Error},
+  events::{EmitEvent, FetchFinishEvent, FetchStartEvent, SeedUnreadCountEvent},
+  extract::extract_content,
+  favicon::{self, fetch_favicon},
+  mute::{MuteAction, MuteFilter},
+  notify::{notify_new_articles, NewArticle},
+  rules::RuleEngine,
+  sanitize::{html_to_text, sanitize_html},
+  scheduler::reschedule,
+  seed::{ChannelInfo, FetchResult, FetchStatus, Seed},
+  watch::Candidate,
 };
 
+/// 自定义请求头中不允许设置的头
+const FORBIDDEN_HEADERS: &[HeaderName] =
+  &[CONNECTION, CONTENT_LENGTH, HOST, TRANSFER_ENCODING, UPGRADE];
+/// 每次抓取时最多自动提取正文的文章数量
+const MAX_AUTO_EXTRACT: usize = 20;
+/// 提取正文时网页大小的上限，字节
+const MAX_PAGE_SIZE: usize = 5 * 1024 * 1024;
+/// 检查网络时最多访问的站点数量
+const MAX_PROBES: usize = 3;
+/// 网络不可用时探测网络的超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 是否正在抓取，定时抓取和手动刷新不能同时进行
+static FETCHING: AtomicBool = AtomicBool::new(false);
+
+/// 抓取期间持有，释放时清除抓取标志
+struct FetchGuard;
+
+impl FetchGuard {
+  /// 开始抓取，已经在抓取时返回 `None`
+  fn acquire() -> Option<Self> {
+    FETCHING
+      .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+      .ok()
+      .map(|_| Self)
+  }
+}
+
+impl Drop for FetchGuard {
+  fn drop(&mut self) {
+    FETCHING.store(false, Ordering::Release);
+  }
+}
+
 /// 代理设置
 #[derive(Debug, Deserialize)]
-struct ProxySettings {
+pub struct ProxySettings {
+  /// 代理类型，`none`、`sys` 或 `http`
   #[serde(rename = "type")]
-  t: String,
-  host: String,
-  port: u16,
+  pub t: String,
+  /// 代理服务器的主机名
+  pub host: String,
+  /// 代理服务器的端口
+  pub port: u16,
 }
 
 /// 一般设置
 #[derive(Debug, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
 pub struct GenericSettings {
   /// 请求超时时间，秒
   pub timeout: u32,
+  /// 图片缓存大小上限，MB
+  #[serde(default = "default_cache_size")]
+  pub cache_size: u32,
+  /// 是否允许下载本机和内网地址的资源
+  #[serde(default)]
+  pub allow_private_network: bool,
+}
+
+/// 默认的图片缓存大小上限，MB
+fn default_cache_size() -> u32 {
+  200
+}
+
+impl GenericSettings {
+  /// 图片缓存大小上限，字节
+  pub fn cache_quota(&self) -> u64 {
+    u64::from(self.cache_size) * 1024 * 1024
+  }
 }
 
 /// 获取代理设置
-fn get_proxy(db: &Connection) -> Result<ProxySettings> {
+pub fn get_proxy(db: &Connection) -> Result<ProxySettings> {
   let mut stmt = db.prepare("SELECT value FROM settings WHERE key = ?1")?;
   let mut rows = stmt.query(["proxy"])?;
 
@@ -54,7 +132,7 @@ fn get_proxy(db: &Connection) -> Result<ProxySettings> {
   }
 }
 
-fn get_generic_settings(db: &Connection) -> Result<GenericSettings> {
+pub fn get_generic_settings(db: &Connection) -> Result<GenericSettings> {
   let mut stmt = db.prepare("SELECT value FROM settings WHERE key = ?1")?;
   let mut rows = stmt.query(["generic"])?;
 
@@ -63,17 +141,29 @@ fn get_generic_settings(db: &Connection) -> Result<GenericSettings> {
     let proxy: GenericSettings = serde_json::from_str(value.as_str())?;
     Ok(proxy)
   } else {
-    Ok(GenericSettings { timeout: 30 })
+    Ok(GenericSettings {
+      timeout: 30,
+      cache_size: default_cache_size(),
+      allow_private_network: false,
+    })
   }
 }
 
-fn get_data(app_handle: &AppHandle) -> Result<(ProxySettings, GenericSettings, Vec<Seed>)> {
-  // 打开数据库
-  let db = initialize(app_handle, true)?;
+/// 获取图片缓存的大小上限，字节
+pub fn get_cache_quota(db: &Connection) -> Result<u64> {
+  Ok(get_generic_settings(db)?.cache_quota())
+}
 
-  let proxy = get_proxy(&db)?;
-  let generic = get_generic_settings(&db)?;
-  let seeds = get_all_seeds(&db)?;
+async fn get_data(app_handle: &AppHandle) -> Result<(ProxySettings, GenericSettings, Vec<Seed>)> {
+  let (proxy, generic, seeds) = app_handle
+    .db(|db| -> Result<_> {
+      Ok((
+        get_proxy(db)?,
+        get_generic_settings(db)?,
+        get_all_seeds(db)?,
+      ))
+    })
+    .await?;
 
   #[cfg(debug_assertions)]
   debug!("Settings: {:?}, {:?}", &proxy, &generic);
@@ -81,17 +171,37 @@ fn get_data(app_handle: &AppHandle) -> Result<(ProxySettings, GenericSettings, V
   Ok((proxy, generic, seeds))
 }
 
-fn insert_items(app_handle: &AppHandle, seed_id: i64, items: &Vec<Item>) -> Result<()> {
-  app_handle.db_mut(|db| -> Result<()> {
+/// 插入新文章，返回插入的未静音文章的 ID 和链接
+///
+/// 文章的 HTML 按允许列表清理后保存，相对 URL 以文章链接为基准，没有链接时以 `base` 为基准，原始内容另外保存。
+async fn insert_items(
+  app_handle: &AppHandle,
+  seed: &Seed,
+  items: Vec<Item>,
+  base: Option<Url>,
+) -> Result<Vec<(i64, String)>> {
+  let seed_id = seed.id;
+
+  let (total, inserted_articles, links) = app_handle
+    .db_mut(move |db| -> Result<_> {
+    let base = base.as_ref();
+    let engine = RuleEngine::new(get_rules(db)?);
+    let mute_filter = MuteFilter::new(get_mute_rules(db)?);
+    let folder_ids = get_seed_folder_ids(db, seed_id)?;
     let tx = db.transaction()?;
     let mut total = 0;
+    let mut inserted_articles = Vec::new();
+    let mut links = Vec::new();
+    let mut muted_counts: HashMap<i64, usize> = HashMap::new();
 
     {
-      let mut stmt = tx.prepare("INSERT OR IGNORE INTO articles (seed_id, guid, title, author, desc, link, pub_date, unread) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")?;
+      let mut stmt = tx.prepare("INSERT OR IGNORE INTO articles (seed_id, guid, title, author, desc, link, pub_date, unread, starred, content, muted_by, raw_desc, raw_content, desc_text, content_text) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)")?;
       let now = Local::now();
-      let deadline = now.checked_sub_days(Days::new(30)).unwrap();
+      let deadline = now
+        .checked_sub_days(Days::new(30))
+        .ok_or_else(|| anyhow!("The article deadline is out of range."))?;
 
-      for item in items {
+      for item in &items {
         let guid = if let Some(guid) = &item.guid {
           Some(guid.value.clone())
         } else {
@@ -102,59 +212,163 @@ fn insert_items(app_handle: &AppHandle, seed_id: i64, items: &Vec<Item>) -> Resu
           let date = DateTime::parse_from_rfc2822(date.as_str())?;
 
           if date > deadline {
+            // 插入之前应用规则和静音过滤器
+            let candidate = Candidate {
+              seed_id,
+              folder_ids: &folder_ids,
+              title: item.title.as_deref(),
+              author: item.author.as_deref(),
+              description: item.description.as_deref(),
+              content: item.content.as_deref(),
+            };
+            let outcome = engine.evaluate(&candidate);
+
+            if outcome.skip {
+              continue;
+            }
+
+            let muted = mute_filter.check(&candidate);
+            let muted_by = match muted {
+              Some((rule_id, MuteAction::Hide)) => Some(rule_id),
+              _ => None,
+            };
+            // 隐藏的文章保留未读状态，取消静音后恢复原样
+            let unread = !outcome.mark_read && !matches!(muted, Some((_, MuteAction::MarkRead)));
+
+            let item_base = match (item.link.as_deref(), base) {
+              (Some(link), Some(base)) => base.join(link).ok(),
+              (Some(link), None) => Url::parse(link).ok(),
+              (None, _) => None,
+            }
+            .or_else(|| base.cloned());
+            let desc = item
+              .description
+              .as_deref()
+              .map(|html| sanitize_html(html, item_base.as_ref()));
+            let content = item
+              .content
+              .as_deref()
+              .map(|html| sanitize_html(html, item_base.as_ref()));
+
             let date = date.timestamp();
             let inserted = stmt.execute(params![
               seed_id,
               guid,
               item.title,
               item.author,
-              item.description,
+              desc,
               item.link,
               date,
-              true,
+              unread,
+              outcome.star,
+              content,
+              muted_by,
+              item.description,
+              item.content,
+              desc.as_deref().map(html_to_text),
+              content.as_deref().map(html_to_text),
             ])?;
-            total += inserted;
+
+            if inserted > 0 {
+              let id = tx.last_insert_rowid();
+
+              for tag in &outcome.tags {
+                tag_article(&tx, id, tag)?;
+              }
+
+              add_article_images(
+                &tx,
+                id,
+                item.link.as_deref(),
+                &[desc.as_deref(), content.as_deref()],
+              )?;
+
+              if unread && muted_by.is_none() {
+                total += inserted;
+              }
+
+              // 静音的文章不通知
+              if let Some((rule_id, _)) = muted {
+                *muted_counts.entry(rule_id).or_default() += inserted;
+              } else {
+                if let Some(link) = &item.link {
+                  links.push((id, link.clone()));
+                }
+
+                inserted_articles.push(NewArticle {
+                  id,
+                  title: item.title.clone(),
+                  notify: outcome.notify,
+                });
+              }
+            }
           }
         };
       }
     }
 
-    tx.commit()?;
-
-    if total > 0 {
-      info!("{total} new articles");
-      app_handle
-        .emit_all(
-          "app://seed/new",
-          SeedUnreadCountEvent {
-            id: Some(seed_id),
-            unread_count: total as i32,
-          },
-        )
-        .unwrap();
-      app_handle
-        .emit_all(
-          "app://seed/new",
-          SeedUnreadCountEvent {
-            id: None,
-            unread_count: total as i32,
-          },
-        )
-        .unwrap();
+    for (rule_id, count) in muted_counts {
+      add_muted_count(&tx, rule_id, count)?;
     }
 
-    Ok(())
+    tx.commit()?;
+    Ok((total, inserted_articles, links))
   })
+  .await?;
+
+  // 通知需要读取设置，在写连接之外进行
+  if let Err(err) = notify_new_articles(app_handle, seed, &inserted_articles).await {
+    warn!("Failed to notify new articles of {}: {:?}", &seed.name, err);
+  }
+
+  if total > 0 {
+    info!("{total} new articles");
+    app_handle.emit_event(
+      "app://seed/new",
+      SeedUnreadCountEvent {
+        id: Some(seed_id),
+        unread_count: total as i32,
+      },
+    );
+    app_handle.emit_event(
+      "app://seed/new",
+      SeedUnreadCountEvent {
+        id: None,
+        unread_count: total as i32,
+      },
+    );
+  }
+
+  Ok(links)
 }
 
-async fn fetch(
-  app_handle: &AppHandle,
-  proxy: &ProxySettings,
-  generic: &GenericSettings,
-  seed: &Seed,
+/// 获取上次抓取时服务器返回的 `ETag` 和 `Last-Modified`
+fn get_validators(db: &Connection, seed_id: i64) -> Result<(Option<String>, Option<String>)> {
+  let validators = db.query_row(
+    "SELECT etag, last_modified FROM seeds WHERE id = ?1",
+    [seed_id],
+    |row| Ok((row.get(0)?, row.get(1)?)),
+  )?;
+  Ok(validators)
+}
+
+/// 保存服务器返回的 `ETag` 和 `Last-Modified`，供下次条件请求使用
+fn save_validators(
+  db: &Connection,
+  seed_id: i64,
+  etag: Option<String>,
+  last_modified: Option<String>,
 ) -> Result<()> {
-  info!("Fetching {}", &seed.name);
-  let mut client = reqwest::Client::builder();
+  db.execute(
+    "UPDATE seeds SET etag = ?2, last_modified = ?3 WHERE id = ?1",
+    params![seed_id, etag, last_modified],
+  )?;
+  Ok(())
+}
+
+/// 按代理设置创建 HTTP 客户端构建器
+pub fn client_builder(proxy: &ProxySettings) -> Result<ClientBuilder> {
+  let mut client = Client::builder();
 
   match proxy.t.as_str() {
     "none" => {
@@ -166,10 +380,164 @@ async fn fetch(
     _ => {}
   }
 
-  let client = client
-    .timeout(std::time::Duration::from_secs(generic.timeout.into()))
-    .build()?;
-  let content = client.get(&seed.url).send().await?.bytes().await?;
+  Ok(client)
+}
+
+/// 按代理设置创建 HTTP 客户端
+fn build_client(proxy: &ProxySettings, timeout: Duration) -> Result<Client> {
+  Ok(client_builder(proxy)?.timeout(timeout).build()?)
+}
+
+/// 按抓取设置创建 HTTP 客户端
+fn build_fetch_client(proxy: &ProxySettings, generic: &GenericSettings) -> Result<Client> {
+  build_client(proxy, Duration::from_secs(generic.timeout.into()))
+}
+
+/// 把前端传来的请求头转换为 [`HeaderMap`]，名称或值无效以及不允许设置的头返回错误
+fn to_header_map(headers: HashMap<String, String>) -> error::Result<HeaderMap> {
+  let mut map = HeaderMap::with_capacity(headers.len());
+
+  for (name, value) in headers {
+    let name = HeaderName::from_bytes(name.as_bytes())
+      .map_err(|_| Error::validation(format!("Invalid header name: {}", name)))?;
+
+    if FORBIDDEN_HEADERS.contains(&name) {
+      return Err(Error::validation(format!(
+        "Header {} is not allowed.",
+        name
+      )));
+    }
+
+    let value = HeaderValue::from_str(&value)
+      .map_err(|_| Error::validation(format!("Invalid value of header {}.", name)))?;
+    map.insert(name, value);
+  }
+
+  Ok(map)
+}
+
+/// 提取频道信息，空字符串视为没有
+fn to_channel_info(channel: &Channel) -> ChannelInfo {
+  let text = |value: &str| {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+  };
+
+  ChannelInfo {
+    title: text(channel.title()),
+    description: text(channel.description()),
+    link: text(channel.link()),
+    language: channel.language().and_then(text),
+    image: channel.image().and_then(|image| text(image.url())),
+    generator: channel.generator().and_then(text),
+    last_build_date: channel
+      .last_build_date()
+      .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+      .map(|date| date.timestamp()),
+  }
+}
+
+/// 保存种子的频道信息
+fn save_channel_info(db: &Connection, seed_id: i64, info: &ChannelInfo) -> Result<()> {
+  db.execute(
+    "UPDATE seeds SET channel_title = ?2, channel_description = ?3, channel_link = ?4, channel_language = ?5, channel_image = ?6, channel_generator = ?7, channel_last_build_date = ?8 WHERE id = ?1",
+    params![
+      seed_id,
+      info.title,
+      info.description,
+      info.link,
+      info.language,
+      info.image,
+      info.generator,
+      info.last_build_date,
+    ],
+  )?;
+  Ok(())
+}
+
+/// 获取种子图标的更新时间，以及保存的频道网站链接和图片 URL
+fn get_favicon_source(
+  db: &Connection,
+  seed_id: i64,
+) -> Result<(i64, Option<String>, Option<String>)> {
+  let source = db.query_row(
+    "SELECT favicon_updated_at, channel_link, channel_image FROM seeds WHERE id = ?1",
+    [seed_id],
+    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+  )?;
+  Ok(source)
+}
+
+/// 保存种子图标。没有找到图标时保留原来的图标，只更新时间，到下个更新周期再尝试
+fn save_favicon(db: &Connection, seed_id: i64, favicon: Option<String>) -> Result<()> {
+  db.execute(
+    "UPDATE seeds SET favicon = COALESCE(?2, favicon), favicon_updated_at = ?3 WHERE id = ?1",
+    params![seed_id, favicon, Local::now().timestamp()],
+  )?;
+  Ok(())
+}
+
+/// 图标过期时重新获取。使用保存的频道信息，订阅源没有变化时也会按周期更新。
+/// 图标只是装饰，失败时不影响抓取
+async fn update_favicon(app_handle: &AppHandle, seed: &Seed) {
+  let result = async {
+    let seed_id = seed.id;
+    let (updated_at, link, image) = app_handle
+      .db(move |db| get_favicon_source(db, seed_id))
+      .await?;
+
+    if Local::now().timestamp() - updated_at < favicon::REFRESH_INTERVAL {
+      return Ok(());
+    }
+
+    // 图标地址来自订阅源，通过下载客户端访问
+    let downloader = Downloader::new(app_handle).await?;
+    let favicon = fetch_favicon(&downloader, link.as_deref(), image.as_deref(), &seed.url).await;
+    debug!("Favicon of {} found: {}", &seed.name, favicon.is_some());
+    app_handle
+      .db_mut(move |db| save_favicon(db, seed_id, favicon))
+      .await
+  }
+  .await;
+
+  if let Err(err) = result {
+    warn!("Failed to update favicon of {}: {:?}", &seed.name, err);
+  }
+}
+
+async fn fetch(app_handle: &AppHandle, client: &Client, seed: &Seed) -> Result<FetchStatus> {
+  info!("Fetching {}", &seed.name);
+  let seed_id = seed.id;
+  let (etag, last_modified) = app_handle.db(move |db| get_validators(db, seed_id)).await?;
+  let mut request = client.get(&seed.url);
+
+  if let Some(etag) = &etag {
+    request = request.header(IF_NONE_MATCH, etag);
+  }
+
+  if let Some(last_modified) = &last_modified {
+    request = request.header(IF_MODIFIED_SINCE, last_modified);
+  }
+
+  let response = request.send().await?;
+
+  if response.status() == StatusCode::NOT_MODIFIED {
+    info!("{} is not modified", &seed.name);
+    update_favicon(app_handle, seed).await;
+    return Ok(FetchStatus::NotModified);
+  }
+
+  let response = response.error_for_status()?;
+  let header = |name| {
+    response
+      .headers()
+      .get(name)
+      .and_then(|value| value.to_str().ok())
+      .map(String::from)
+  };
+  let etag = header(ETAG);
+  let last_modified = header(LAST_MODIFIED);
+  let content = response.bytes().await?;
 
   // #[cfg(debug_assertions)]
   // {
@@ -179,87 +547,458 @@ async fn fetch(
 
   let channel = Channel::read_from(&content[..])?;
   #[cfg(debug_assertions)]
-  debug!("First item {:?}", &channel.items[0]);
-
-  insert_items(app_handle, seed.id, &channel.items)?;
+  debug!("First item {:?}", channel.items.first());
+
+  // 相对 URL 以订阅源的网站地址为基准，没有网站地址时以订阅源的地址为基准
+  let base = Url::parse(&seed.url)
+    .ok()
+    .map(|feed_url| feed_url.join(channel.link()).unwrap_or(feed_url));
+  let channel_info = to_channel_info(&channel);
+  let links = insert_items(app_handle, seed, channel.items, base).await?;
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      save_validators(db, seed_id, etag, last_modified)?;
+      save_channel_info(db, seed_id, &channel_info)
+    })
+    .await?;
+  update_favicon(app_handle, seed).await;
+
+  if seed.auto_extract && !links.is_empty() {
+    // 文章链接来自订阅源，和图片一样通过下载客户端访问
+    let downloader = Downloader::new(app_handle).await?;
+
+    for (id, link) in links.iter().take(MAX_AUTO_EXTRACT) {
+      if let Err(err) = extract(
+        app_handle,
+        &downloader,
+        *id,
+        link,
+        seed.extract_selector.as_deref(),
+      )
+      .await
+      {
+        warn!("Failed to extract {}: {:?}", link, err);
+      }
+    }
+  }
   info!("Fetched {}", &seed.name);
 
-  Ok(())
+  Ok(FetchStatus::Ok)
 }
 
-fn save_last_fetch(app_handle: &AppHandle, seed_id: i64, ok: bool) -> Result<()> {
-  let db = initialize(app_handle, false)?;
-  let mut stmt =
-    db.prepare("UPDATE seeds SET last_fetched_at = ?2, last_fetch_ok = ?3 WHERE id = ?1")?;
-  stmt.execute(params![seed_id, Local::now().timestamp(), ok])?;
-  Ok(())
+/// 下载网页，最多读取 [`MAX_PAGE_SIZE`] 字节，按响应声明的字符集解码。返回重定向后的地址及网页内容
+async fn download_page(downloader: &Downloader, url: &str) -> Result<(Url, String)> {
+  let url = Url::parse(url)?;
+  downloader.check_url(&url)?;
+
+  let response = downloader
+    .client
+    .get(url)
+    .send()
+    .await?
+    .error_for_status()?;
+  let base = response.url().clone();
+  let encoding = response
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| {
+      value
+        .split(';')
+        .filter_map(|param| param.trim().split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case("charset"))
+        .and_then(|(_, charset)| Encoding::for_label(charset.trim_matches('"').as_bytes()))
+    })
+    .unwrap_or(UTF_8);
+  let (data, truncated) = read_limited(response, MAX_PAGE_SIZE).await?;
+
+  if truncated {
+    bail!("{} is too large.", base);
+  }
+
+  let (html, _, _) = encoding.decode(&data);
+  Ok((base, html.into_owned()))
+}
+
+/// 提取文章链接网页中的正文，保存为文章的内容。没有找到正文时返回 `None`
+async fn extract(
+  app_handle: &AppHandle,
+  downloader: &Downloader,
+  article_id: i64,
+  link: &str,
+  selector: Option<&str>,
+) -> Result<Option<String>> {
+  debug!("Extracting {}", link);
+  // 重定向后以最终的地址解析相对 URL
+  let (base, html) = download_page(downloader, link).await?;
+  let content = extract_content(&html, &base, selector)?;
+
+  if let Some(content) = &content {
+    let content = content.clone();
+    let link = link.to_string();
+    app_handle
+      .db_mut(move |db| -> Result<()> {
+        db.execute(
+          "UPDATE articles SET content = ?2, content_text = ?3 WHERE id = ?1",
+          params![article_id, content, html_to_text(&content)],
+        )?;
+        add_article_images(db, article_id, Some(&link), &[Some(&content)])
+      })
+      .await?;
+  }
+
+  Ok(content)
 }
 
-pub async fn check_seeds() -> Result<()> {
-  if let Some(app_handle) = get_app_handle() {
-    // 读取代理设置和种子
-    let (proxy, generic, seeds) = get_data(&app_handle)?;
+async fn save_last_fetch(app_handle: &AppHandle, result: &FetchResult) -> Result<()> {
+  let params = (
+    result.seed_id,
+    result.fetched_at,
+    result.status != FetchStatus::Error,
+    result.error.clone(),
+  );
+
+  app_handle
+    .db_mut(move |db| -> Result<()> {
+      let mut stmt = db.prepare(
+        "UPDATE seeds SET last_fetched_at = ?2, last_fetch_ok = ?3, last_fetch_error = ?4 WHERE id = ?1",
+      )?;
+      stmt.execute(params)?;
+      Ok(())
+    })
+    .await
+}
 
-    for seed in seeds {
-      if seed.should_fetch() {
-        // 抓取
-        if let Err(err) = fetch(&app_handle, &proxy, &generic, &seed).await {
-          warn!("Failed to fetch {}: {:?}", &seed.name, err);
-          save_last_fetch(&app_handle, seed.id, false)?;
-        } else {
-          save_last_fetch(&app_handle, seed.id, true)?;
-        }
+/// 是否是无法连接服务器造成的错误，如 DNS 解析失败、连接被拒绝或超时
+fn is_connect_error(err: &anyhow::Error) -> bool {
+  err
+    .downcast_ref::<reqwest::Error>()
+    .is_some_and(|err| err.is_connect() || err.is_timeout())
+}
+
+/// URL 的主机名
+pub fn host(url: &str) -> Option<String> {
+  Url::parse(url).ok()?.host_str().map(String::from)
+}
+
+/// 访问其他站点的种子，检查网络是否可用。没有可供检查的站点时认为网络可用。
+async fn is_online(app_handle: &AppHandle, client: &Client, failed: &[Seed]) -> Result<bool> {
+  let mut hosts: HashSet<_> = failed.iter().filter_map(|seed| host(&seed.url)).collect();
+  let seeds = app_handle.db(get_all_seeds).await?;
+  let mut probed = 0;
+
+  for seed in seeds.iter().filter(|seed| seed.last_fetch_ok) {
+    if probed >= MAX_PROBES {
+      break;
+    }
+
+    let Some(host) = host(&seed.url) else {
+      continue;
+    };
+
+    if !hosts.insert(host) {
+      continue;
+    }
+
+    probed += 1;
+
+    // 收到任何响应都说明网络可用
+    if client.head(&seed.url).send().await.is_ok() {
+      return Ok(true);
+    }
+  }
+
+  Ok(probed == 0)
+}
+
+/// 网络不可用时探测网络是否已经恢复：访问第一个种子，收到任何响应都说明网络可用
+pub async fn probe_network(app_handle: &AppHandle, seed_ids: &[i64]) -> Result<bool> {
+  let (proxy, _, seeds) = get_data(app_handle).await?;
+  let Some(seed) = seeds.iter().find(|seed| seed_ids.contains(&seed.id)) else {
+    return Ok(true);
+  };
+  let client = build_client(&proxy, PROBE_TIMEOUT)?;
+
+  Ok(client.head(&seed.url).send().await.is_ok())
+}
+
+/// 依次抓取种子并记录结果。
+///
+/// 所有种子都无法连接、其他站点也无法访问时，认为网络不可用，不记录这些失败并返回 `None`。
+async fn fetch_seeds(
+  app_handle: &AppHandle,
+  client: &Client,
+  seeds: &[Seed],
+) -> Result<Option<Vec<FetchResult>>> {
+  let mut results = Vec::with_capacity(seeds.len());
+  // 无法连接的种子，确认网络可用后再记录并发送事件
+  let mut unreachable = Vec::new();
+  let mut finish = FetchFinishEvent {
+    ok: 0,
+    not_modified: 0,
+    failed: 0,
+    offline: 0,
+  };
+
+  app_handle.emit_event(
+    "app://fetch/start",
+    FetchStartEvent {
+      seed_ids: seeds.iter().map(|seed| seed.id).collect(),
+    },
+  );
+
+  for seed in seeds {
+    let (status, error, connect_error) = match fetch(app_handle, client, seed).await {
+      Ok(status) => (status, None, false),
+      Err(err) => {
+        warn!("Failed to fetch {}: {:?}", &seed.name, err);
+        let connect_error = is_connect_error(&err);
+        (
+          FetchStatus::Error,
+          Some(Error::from(err).to_string()),
+          connect_error,
+        )
       }
+    };
+    let result = FetchResult {
+      seed_id: seed.id,
+      status,
+      error,
+      fetched_at: Local::now().timestamp(),
+    };
+
+    if connect_error {
+      unreachable.push(result);
+    } else {
+      save_last_fetch(app_handle, &result).await?;
+      finish.count(status);
+      app_handle.emit_event("app://fetch/seed", result.clone());
+      results.push(result);
     }
   }
 
-  Ok(())
+  // 所有种子都无法连接时检查网络，网络不可用时不记录结果，种子标记为跳过
+  let offline =
+    !unreachable.is_empty() && results.is_empty() && !is_online(app_handle, client, seeds).await?;
+
+  for mut result in unreachable {
+    if offline {
+      result.status = FetchStatus::Offline;
+    } else {
+      save_last_fetch(app_handle, &result).await?;
+    }
+
+    finish.count(result.status);
+    app_handle.emit_event("app://fetch/seed", result.clone());
+    results.push(result);
+  }
+
+  app_handle.emit_event("app://fetch/finish", finish);
+
+  if offline {
+    warn!("The network is unavailable");
+    return Ok(None);
+  }
+
+  Ok(Some(results))
+}
+
+/// 定时抓取的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledFetch {
+  /// 已抓取
+  Done,
+  /// 正在手动刷新，没有抓取
+  Busy,
+  /// 网络不可用
+  Offline,
+}
+
+/// 定时抓取到期的种子
+pub async fn fetch_scheduled(app_handle: &AppHandle, seed_ids: &[i64]) -> Result<ScheduledFetch> {
+  let Some(_guard) = FetchGuard::acquire() else {
+    return Ok(ScheduledFetch::Busy);
+  };
+
+  // 读取代理设置和种子
+  let (proxy, generic, seeds) = get_data(app_handle).await?;
+  let seeds: Vec<_> = seeds
+    .into_iter()
+    .filter(|seed| seed_ids.contains(&seed.id))
+    .collect();
+  let client = build_fetch_client(&proxy, &generic)?;
+
+  match fetch_seeds(app_handle, &client, &seeds).await? {
+    Some(_) => {
+      prefetch(app_handle.clone());
+      Ok(ScheduledFetch::Done)
+    }
+    None => Ok(ScheduledFetch::Offline),
+  }
 }
 
-/// 下载指定 URL 的数据
+/// 立即抓取种子，不考虑更新周期。指定 `seed_id` 时只抓取该种子，指定 `folder_id` 时抓取文件夹（含子文件夹）下的种子，都为空时抓取所有种子。
+///
+/// 指定文件夹或抓取所有种子时跳过已暂停的种子。
 #[tauri::command]
 #[specta::specta]
-pub async fn download(
+pub async fn refresh(
   app_handle: AppHandle,
-  url: String,
-  referer: Option<String>,
-) -> crate::error::Result<String> {
-  debug!("Downloading {}", &url);
-  let proxy = app_handle.db(|db| -> Result<ProxySettings> { get_proxy(db) })?;
-  let mut client = reqwest::Client::builder();
+  seed_id: Option<i64>,
+  folder_id: Option<i64>,
+) -> error::Result<Vec<FetchResult>> {
+  let Some(guard) = FetchGuard::acquire() else {
+    return Err(Error::busy("Seeds are being fetched."));
+  };
 
-  match proxy.t.as_str() {
-    "none" => {
-      client = client.no_proxy();
+  info!("Refresh: {:?}, {:?}", seed_id, folder_id);
+  let (proxy, generic, seeds) = get_data(&app_handle).await?;
+  let seeds: Vec<_> = match (seed_id, folder_id) {
+    (Some(seed_id), _) => {
+      let seeds: Vec<_> = seeds
+        .into_iter()
+        .filter(|seed| seed.id == seed_id)
+        .collect();
+
+      if seeds.is_empty() {
+        return Err(Error::not_found("The seed does not exist."));
+      }
+
+      seeds
     }
-    "http" => {
-      client =
-        client.proxy(Proxy::all(format!("http://{}:{}", proxy.host, proxy.port)).into_result()?);
+    (None, Some(folder_id)) => {
+      let ids = app_handle
+        .db(move |db| get_folder_seed_ids(db, folder_id))
+        .await?;
+      seeds
+        .into_iter()
+        .filter(|seed| !seed.paused && ids.contains(&seed.id))
+        .collect()
     }
-    _ => {}
+    (None, None) => seeds.into_iter().filter(|seed| !seed.paused).collect(),
+  };
+
+  let client = build_fetch_client(&proxy, &generic)?;
+  let results = fetch_seeds(&app_handle, &client, &seeds).await;
+
+  if let Ok(Some(_)) = &results {
+    prefetch(app_handle.clone());
   }
 
-  let client = client
-    .timeout(std::time::Duration::from_secs(30))
-    .build()
-    .into_result()?;
-  let mut request = client.get(url);
+  // 先结束抓取，调度器才能按新的抓取时间继续
+  drop(guard);
+  reschedule();
+
+  results?.ok_or_else(|| Error::Network("The network is unavailable.".to_string()))
+}
+
+/// 读取订阅源的频道信息，用于添加种子时自动填写名称
+#[tauri::command]
+#[specta::specta]
+pub async fn fetch_channel_info(app_handle: AppHandle, url: String) -> error::Result<ChannelInfo> {
+  debug!("Fetching channel info of {}", &url);
+  let (proxy, generic) = app_handle
+    .db(|db| -> Result<_> { Ok((get_proxy(db)?, get_generic_settings(db)?)) })
+    .await?;
+  let client = build_fetch_client(&proxy, &generic)?;
+  let content = client
+    .get(url)
+    .send()
+    .await?
+    .error_for_status()?
+    .bytes()
+    .await?;
+  let channel = Channel::read_from(&content[..])?;
+
+  Ok(to_channel_info(&channel))
+}
+
+/// 提取文章的正文，使用种子的 CSS 选择器或自动查找正文，保存为文章的内容并返回。
+#[tauri::command]
+#[specta::specta]
+pub async fn extract_article(app_handle: AppHandle, article_id: i64) -> error::Result<String> {
+  let (link, selector): (Option<String>, Option<String>) = app_handle
+    .db(move |db| {
+      db.query_row(
+        "SELECT articles.link, seeds.extract_selector FROM articles LEFT JOIN seeds ON articles.seed_id = seeds.id WHERE articles.id = ?1",
+        [article_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+    })
+    .await?;
+  let link = link.ok_or_else(|| Error::not_found("The article has no link."))?;
+  let downloader = Downloader::new(&app_handle).await?;
+  let content = extract(
+    &app_handle,
+    &downloader,
+    article_id,
+    &link,
+    selector.as_deref(),
+  )
+  .await?;
+
+  content.ok_or_else(|| Error::not_found("No content is found."))
+}
+
+/// 下载指定 URL 的图片，返回 data URL。优先使用图片缓存，下载后加入缓存。
+///
+/// 只允许 HTTP(S)，默认不允许本机和内网地址。`headers` 为额外的请求头。
+#[tauri::command]
+#[specta::specta]
+pub async fn download(
+  app_handle: AppHandle,
+  url: String,
+  referer: Option<String>,
+  headers: Option<HashMap<String, String>>,
+) -> error::Result<String> {
+  let parsed = Url::parse(&url).map_err(|_| Error::validation("Invalid URL."))?;
+  let mut headers = to_header_map(headers.unwrap_or_default())?;
 
   if let Some(referer) = referer {
-    request = request.header(REFERER, referer);
+    let referer =
+      HeaderValue::from_str(&referer).map_err(|_| Error::validation("Invalid referer."))?;
+    headers.insert(REFERER, referer);
   }
 
-  let response = request.send().await.into_result()?;
-  let content_type = {
-    let value = response.headers().get(CONTENT_TYPE);
+  let downloader = Downloader::new(&app_handle).await?;
+  downloader.check_url(&parsed)?;
+
+  // 读取缓存是同步的，放到阻塞线程上
+  let handle = app_handle.clone();
+  let key = url.clone();
+  let cached = spawn_blocking(move || get_cached(&handle, &key))
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result)
+    .unwrap_or_else(|err| {
+      warn!("Failed to read the image cache: {:?}", err);
+      None
+    });
+
+  let (content_type, body) = match cached {
+    Some(image) => (image.content_type, image.data),
+    None => {
+      debug!("Downloading {}", &url);
+      let (content_type, body) = downloader
+        .get_image(&parsed, headers, MAX_IMAGE_SIZE)
+        .await?;
+
+      if let Err(err) = store(
+        &app_handle,
+        &url,
+        &content_type,
+        body.clone(),
+        downloader.cache_quota,
+      )
+      .await
+      {
+        warn!("Failed to cache {}: {:?}", &url, err);
+      }
 
-    if let Some(value) = value {
-      String::from(value.to_str().into_result()?)
-    } else {
-      String::from("image/jpeg")
+      (content_type, body)
     }
   };
-  let body = response.bytes().await.into_result()?;
+
   let encoded = STANDARD.encode(&body);
   Ok(format!("data:{};base64,{}", content_type, encoded))
 }
@@ -2,19 +2,41 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app_handle;
+mod cache;
 mod db;
+mod download;
 mod error;
 mod events;
+mod extract;
+mod favicon;
 mod job;
+mod media;
+mod migrations;
+mod mute;
+mod notify;
+mod pool;
+mod rules;
+mod sanitize;
+mod scheduler;
 mod seed;
+mod watch;
 
 use app_handle::set_app_handle;
 use db::{
-  db_add_watch_keyword, db_delete_watch_keyword, db_get_all_seeds, db_get_articles, db_get_setting,
-  db_get_unread_count, db_get_watch_list, db_insert_seed, db_read_all, db_read_article,
-  db_set_setting, db_update_seed, initialize, optimize, AppState,
+  db_add_watch_keyword, db_apply_rule, db_delete_folder, db_delete_mute_rule, db_delete_rule,
+  db_delete_saved_search, db_delete_tag, db_delete_watch_keyword, db_delete_watch_rule,
+  db_get_all_folders, db_get_all_seeds, db_get_article, db_get_articles,
+  db_get_folder_unread_count, db_get_mute_rules, db_get_rules, db_get_saved_searches,
+  db_get_setting, db_get_tags, db_get_unread_count, db_get_watch_list, db_get_watch_rules,
+  db_insert_folder, db_insert_saved_search, db_insert_seed, db_move_folder, db_move_seed,
+  db_read_all, db_read_article, db_read_folder, db_rename_folder, db_save_mute_rule, db_save_rule,
+  db_save_watch_rule, db_set_seed_extract, db_set_seed_notify, db_set_seed_paused, db_set_setting,
+  db_star_article, db_tag_article, db_unmute_article, db_untag_article, db_update_saved_search,
+  db_update_seed, open_pool, optimize, AppState,
 };
-use job::{check_seeds, download};
+use job::{download, extract_article, fetch_channel_info, refresh};
+use log::error;
+use notify::get_notified_article;
 use tauri::{
   async_runtime::spawn, AppHandle, CustomMenuItem, Manager, State, SystemTray, SystemTrayEvent,
   SystemTrayMenu, SystemTrayMenuItem, WindowBuilder,
@@ -24,10 +46,21 @@ use tokio_schedule::{every, Job};
 #[cfg(debug_assertions)]
 fn export_bindings() {
   //use job::GenericSettings;
+  use std::{fs::OpenOptions, io::Write};
+
   use specta::{collect_types, ts::BigIntExportBehavior};
   use tauri_specta::ts;
 
+  use crate::events::{FetchFinishEvent, FetchStartEvent, MediaCachedEvent};
+
   let config = specta::ts::ExportConfiguration::new().bigint(BigIntExportBehavior::Number);
+  // 命令返回的错误类型和事件类型不会随命令导出，需要单独导出
+  let extra_types = [
+    specta::ts::export::<error::Error>(&config).unwrap(),
+    specta::ts::export::<FetchStartEvent>(&config).unwrap(),
+    specta::ts::export::<FetchFinishEvent>(&config).unwrap(),
+    specta::ts::export::<MediaCachedEvent>(&config).unwrap(),
+  ];
 
   // println!(
   //   "{}",
@@ -37,32 +70,77 @@ fn export_bindings() {
   ts::export_with_cfg(
     collect_types![
       db_add_watch_keyword,
+      db_apply_rule,
+      db_delete_folder,
+      db_delete_mute_rule,
+      db_delete_rule,
+      db_delete_saved_search,
+      db_delete_tag,
       db_delete_watch_keyword,
+      db_delete_watch_rule,
+      db_get_all_folders,
       db_get_all_seeds,
+      db_get_article,
       db_get_articles,
+      db_get_folder_unread_count,
+      db_get_mute_rules,
+      db_get_rules,
+      db_get_saved_searches,
       db_get_setting,
+      db_get_tags,
       db_get_unread_count,
       db_get_watch_list,
+      db_get_watch_rules,
+      db_insert_folder,
+      db_insert_saved_search,
       db_insert_seed,
-      db_read_article,
+      db_move_folder,
+      db_move_seed,
       db_read_all,
+      db_read_article,
+      db_read_folder,
+      db_rename_folder,
+      db_save_mute_rule,
+      db_save_rule,
+      db_save_watch_rule,
+      db_set_seed_extract,
+      db_set_seed_notify,
+      db_set_seed_paused,
       db_set_setting,
+      db_star_article,
+      db_tag_article,
+      db_unmute_article,
+      db_untag_article,
+      db_update_saved_search,
       db_update_seed,
       download,
+      extract_article,
+      fetch_channel_info,
+      get_notified_article,
+      refresh,
     ]
     .unwrap(),
     config,
     "../src/lib/bindings.ts",
   )
   .unwrap();
+
+  let mut bindings = OpenOptions::new()
+    .append(true)
+    .open("../src/lib/bindings.ts")
+    .unwrap();
+
+  for extra_type in extra_types {
+    writeln!(bindings, "{}", extra_type).unwrap();
+  }
 }
 
 fn show_main_window(app: &AppHandle) -> tauri::Result<()> {
   if let Some(window) = app.get_window("main") {
     window.show()?;
     window.set_focus()?;
-  } else {
-    WindowBuilder::from_config(app, app.config().tauri.windows.get(0).unwrap().clone()).build()?;
+  } else if let Some(config) = app.config().tauri.windows.first() {
+    WindowBuilder::from_config(app, config.clone()).build()?;
   }
 
   Ok(())
@@ -74,24 +152,18 @@ fn main() {
 
   env_logger::init();
 
-  #[cfg(debug_assertions)]
-  let task = every(10).seconds().perform(|| async {
-    let _ = check_seeds().await;
-  });
-
-  #[cfg(not(debug_assertions))]
-  let task = every(1).minute().perform(|| async {
-    let _ = check_seeds().await;
-  });
-
-  spawn(task);
-
   // 每小时优化一次数据库
   let optimze_task = every(1).hour().perform(|| async {
-    optimize();
+    optimize().await;
   });
   spawn(optimze_task);
 
+  // 每十分钟维护一次图片缓存
+  let cache_task = every(10).minutes().perform(|| async {
+    cache::maintain().await;
+  });
+  spawn(cache_task);
+
   let exit = CustomMenuItem::new("exit".to_string(), "Exit");
   let show = CustomMenuItem::new("show".to_string(), "Show");
   let tray_menu = SystemTrayMenu::new()
@@ -110,39 +182,87 @@ fn main() {
     .manage(AppState {
       db: Default::default(),
     })
+    .register_uri_scheme_protocol(media::SCHEME, media::handle)
     .invoke_handler(tauri::generate_handler![
       db_add_watch_keyword,
+      db_apply_rule,
+      db_delete_folder,
+      db_delete_mute_rule,
+      db_delete_rule,
+      db_delete_saved_search,
+      db_delete_tag,
       db_delete_watch_keyword,
+      db_delete_watch_rule,
+      db_get_all_folders,
       db_get_all_seeds,
+      db_get_article,
       db_get_articles,
+      db_get_folder_unread_count,
+      db_get_mute_rules,
+      db_get_rules,
+      db_get_saved_searches,
       db_get_setting,
+      db_get_tags,
       db_get_unread_count,
       db_get_watch_list,
+      db_get_watch_rules,
+      db_insert_folder,
+      db_insert_saved_search,
       db_insert_seed,
-      db_read_article,
+      db_move_folder,
+      db_move_seed,
       db_read_all,
+      db_read_article,
+      db_read_folder,
+      db_rename_folder,
+      db_save_mute_rule,
+      db_save_rule,
+      db_save_watch_rule,
+      db_set_seed_extract,
+      db_set_seed_notify,
+      db_set_seed_paused,
       db_set_setting,
+      db_star_article,
+      db_tag_article,
+      db_unmute_article,
+      db_untag_article,
+      db_update_saved_search,
       db_update_seed,
       download,
+      extract_article,
+      fetch_channel_info,
+      get_notified_article,
+      refresh,
     ])
     .setup(|app| {
       let handle = app.handle();
       set_app_handle(&handle);
 
+      // 数据库打不开时仍然启动，命令会返回数据库错误，由界面提示用户
       let state: State<AppState> = handle.state();
-      let db = initialize(&handle, false).expect("Failed to initialize database");
-      *state.db.lock().unwrap() = Some(db);
+
+      match open_pool(&handle) {
+        Ok(pool) => {
+          let _ = state.db.set(pool);
+          spawn(scheduler::run(handle.clone()));
+        }
+        Err(err) => error!("Failed to open the database: {:?}", err),
+      }
 
       Ok(())
     })
     .system_tray(tray)
     .on_system_tray_event(|app, event| match event {
       SystemTrayEvent::DoubleClick { .. } => {
-        show_main_window(app).unwrap();
+        if let Err(err) = show_main_window(app) {
+          error!("Failed to show the main window: {:?}", err);
+        }
       }
       SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
         "show" => {
-          show_main_window(app).unwrap();
+          if let Err(err) = show_main_window(app) {
+            error!("Failed to show the main window: {:?}", err);
+          }
         }
         "exit" => {
           app.exit(0);
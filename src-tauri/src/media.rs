@@ -0,0 +1,177 @@
+use std::error::Error as StdError;
+
+use log::warn;
+use reqwest::{
+  header::{ACCEPT_RANGES, CACHE_CONTROL, CONTENT_RANGE, RANGE},
+  StatusCode, Url,
+};
+use tauri::{
+  http::{Request, Response, ResponseBuilder},
+  AppHandle,
+};
+
+use crate::cache::{cache_later, get_cached, CachedImage};
+
+/// 自定义协议的名称
+pub const SCHEME: &str = "rssrs-media";
+/// 每次响应的最大字节数。没有指定结束位置的范围请求会被截断，播放器会继续请求后面的部分
+const MAX_CHUNK: u64 = 4 * 1024 * 1024;
+
+/// 请求的字节范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteRange {
+  /// 从 `start` 到 `end`（含），没有 `end` 时到结尾
+  From(u64, Option<u64>),
+  /// 最后的若干字节
+  Suffix(u64),
+}
+
+impl ByteRange {
+  /// 解析 `Range` 头，只支持单个范围
+  fn parse(value: &str) -> Option<Self> {
+    let (start, end) = value.trim().strip_prefix("bytes=")?.split_once('-')?;
+
+    if end.contains(',') {
+      return None;
+    }
+
+    match (start.trim(), end.trim()) {
+      ("", suffix) => suffix.parse().ok().map(Self::Suffix),
+      (start, "") => start.parse().ok().map(|start| Self::From(start, None)),
+      (start, end) => {
+        let start = start.parse().ok()?;
+        let end = end.parse().ok()?;
+        (start <= end).then_some(Self::From(start, Some(end)))
+      }
+    }
+  }
+
+  /// 按资源长度计算实际的范围（含结尾），每次最多 [`MAX_CHUNK`] 字节。范围无效时返回 `None`
+  fn resolve(self, length: u64) -> Option<(u64, u64)> {
+    let (start, end) = match self {
+      Self::From(start, end) => (start, end.unwrap_or(u64::MAX)),
+      Self::Suffix(0) => return None,
+      Self::Suffix(suffix) => (length.saturating_sub(suffix), u64::MAX),
+    };
+
+    if start >= length {
+      return None;
+    }
+
+    Some((
+      start,
+      end.min(length - 1).min(start.saturating_add(MAX_CHUNK - 1)),
+    ))
+  }
+}
+
+/// 协议的响应
+struct Media {
+  status: u16,
+  content_type: String,
+  content_range: Option<String>,
+  body: Vec<u8>,
+}
+
+impl Media {
+  /// 没有内容的响应
+  fn empty(status: StatusCode) -> Self {
+    Self {
+      status: status.as_u16(),
+      content_type: String::from("text/plain"),
+      content_range: None,
+      body: Vec::new(),
+    }
+  }
+}
+
+/// 从请求的 URL 中取出资源地址和来源页面，只允许 HTTP(S) 资源
+fn parse_request(uri: &str) -> Option<(Url, Option<String>)> {
+  let uri = Url::parse(uri).ok()?;
+  let mut url = None;
+  let mut referer = None;
+
+  for (key, value) in uri.query_pairs() {
+    match key.as_ref() {
+      "url" => url = Url::parse(&value).ok(),
+      "referer" => referer = Some(value.into_owned()),
+      _ => {}
+    }
+  }
+
+  url
+    .filter(|url| matches!(url.scheme(), "http" | "https"))
+    .map(|url| (url, referer))
+}
+
+/// 返回缓存的图片，按请求截取范围
+fn serve_cached(image: CachedImage, range: Option<ByteRange>) -> Media {
+  let length = image.data.len() as u64;
+
+  let Some(range) = range else {
+    return Media {
+      status: StatusCode::OK.as_u16(),
+      content_type: image.content_type,
+      content_range: None,
+      body: image.data,
+    };
+  };
+
+  match range.resolve(length) {
+    Some((start, end)) => Media {
+      status: StatusCode::PARTIAL_CONTENT.as_u16(),
+      content_type: image.content_type,
+      content_range: Some(format!("bytes {}-{}/{}", start, end, length)),
+      body: image.data[start as usize..=end as usize].to_vec(),
+    },
+    None => Media {
+      content_range: Some(format!("bytes */{}", length)),
+      ..Media::empty(StatusCode::RANGE_NOT_SATISFIABLE)
+    },
+  }
+}
+
+/// 处理 `rssrs-media://` 请求。查询参数 `url` 为资源地址，`referer` 为可选的来源页面。
+///
+/// 协议处理在界面线程上同步执行，因此只返回已经缓存的图片，不在这里访问网络。
+/// 没有缓存时返回 404，并在后台下载，缓存后发送 `app://media/cached` 事件，界面据此重新加载。
+pub fn handle(app_handle: &AppHandle, request: &Request) -> Result<Response, Box<dyn StdError>> {
+  let media = match parse_request(request.uri()) {
+    Some((url, referer)) => {
+      let range = request
+        .headers()
+        .get(RANGE.as_str())
+        .and_then(|value| value.to_str().ok())
+        .and_then(ByteRange::parse);
+      let cached = get_cached(app_handle, url.as_str()).unwrap_or_else(|err| {
+        warn!("Failed to read the image cache: {:?}", err);
+        None
+      });
+
+      match cached {
+        Some(image) => serve_cached(image, range),
+        None => {
+          cache_later(app_handle, url, referer);
+          Media::empty(StatusCode::NOT_FOUND)
+        }
+      }
+    }
+    None => Media::empty(StatusCode::BAD_REQUEST),
+  };
+
+  let mut response = ResponseBuilder::new()
+    .status(media.status)
+    .mimetype(&media.content_type)
+    .header(ACCEPT_RANGES.as_str(), "bytes");
+
+  if media.status == StatusCode::NOT_FOUND.as_u16() {
+    // 缓存完成后重新加载时不能使用这次的响应
+    response = response.header(CACHE_CONTROL.as_str(), "no-store");
+  }
+
+  if let Some(content_range) = media.content_range {
+    response = response.header(CONTENT_RANGE.as_str(), content_range);
+  }
+
+  response.body(media.body)
+}
@@ -0,0 +1,780 @@
+use log::info;
+use reqwest::Url;
+use rusqlite::{params, Connection, Result, Transaction};
+
+use crate::sanitize::{html_to_text, sanitize_html};
+
+/// 数据库迁移
+struct Migration {
+  /// 迁移后的版本
+  version: u32,
+  /// 迁移步骤，在事务中执行
+  up: fn(&Transaction) -> Result<()>,
+}
+
+/// 所有迁移，按版本从低到高排列。已发布的迁移不可修改，变更数据库结构时只能追加新的迁移。
+const MIGRATIONS: &[Migration] = &[
+  Migration {
+    version: 4,
+    up: create_tables,
+  },
+  Migration {
+    version: 5,
+    up: add_starred,
+  },
+  Migration {
+    version: 6,
+    up: add_folders,
+  },
+  Migration {
+    version: 7,
+    up: add_tags,
+  },
+  Migration {
+    version: 8,
+    up: add_full_text,
+  },
+  Migration {
+    version: 9,
+    up: add_saved_searches,
+  },
+  Migration {
+    version: 10,
+    up: add_watch_options,
+  },
+  Migration {
+    version: 11,
+    up: add_seed_notify,
+  },
+  Migration {
+    version: 12,
+    up: add_rules,
+  },
+  Migration {
+    version: 13,
+    up: add_mute_rules,
+  },
+  Migration {
+    version: 14,
+    up: add_fetch_status,
+  },
+  Migration {
+    version: 15,
+    up: add_seed_paused,
+  },
+  Migration {
+    version: 16,
+    up: add_favicon_updated_at,
+  },
+  Migration {
+    version: 17,
+    up: add_channel_info,
+  },
+  Migration {
+    version: 18,
+    up: add_extract_options,
+  },
+  Migration {
+    version: 19,
+    up: add_image_cache,
+  },
+  Migration {
+    version: 20,
+    up: add_raw_html,
+  },
+  Migration {
+    version: 21,
+    up: scope_watch_keywords,
+  },
+  Migration {
+    version: 22,
+    up: add_image_failures,
+  },
+  Migration {
+    version: 23,
+    up: index_plain_text,
+  },
+];
+
+/// 将数据库升级到当前版本。每个迁移在单独的事务中执行，并同时更新 `user_version`。
+pub fn upgrade_if_needed(db: &mut Connection, existing_version: u32) -> Result<()> {
+  for migration in MIGRATIONS
+    .iter()
+    .filter(|migration| migration.version > existing_version)
+  {
+    info!("Migrating database to version {}", migration.version);
+
+    let tx = db.transaction()?;
+    (migration.up)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()?;
+  }
+
+  Ok(())
+}
+
+/// 版本 4 及更早的数据库结构
+fn create_tables(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE IF NOT EXISTS seeds (
+      id INTEGER PRIMARY KEY,
+      name TEXT NOT NULL UNIQUE,
+      url TEXT NOT NULL UNIQUE,
+      favicon TEXT,
+      interval INTEGER,
+      last_fetched_at INTEGER,
+      last_fetch_ok INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS articles (
+      id INTEGER PRIMARY KEY,
+      seed_id INTEGER NOT NULL REFERENCES seeds (id) ON DELETE CASCADE ON UPDATE CASCADE,
+      guid TEXT NOT NULL UNIQUE,
+      title TEXT,
+      author TEXT,
+      desc TEXT,
+      link TEXT,
+      pub_date INTEGER NOT NULL,
+      unread INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS articles_pub_date ON articles (pub_date DESC);
+    CREATE TABLE IF NOT EXISTS settings (
+      key TEXT PRIMARY KEY,
+      value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS watch_list (
+      id INTEGER PRIMARY KEY,
+      keyword TEXT NOT NULL UNIQUE
+    );
+    ",
+  )
+}
+
+/// 版本 5 增加了星标
+fn add_starred(tx: &Transaction) -> Result<()> {
+  tx.execute_batch("ALTER TABLE articles ADD COLUMN starred INTEGER NOT NULL DEFAULT 0;")
+}
+
+/// 版本 6 增加了文件夹
+fn add_folders(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE folders (
+      id INTEGER PRIMARY KEY,
+      name TEXT NOT NULL,
+      parent_id INTEGER REFERENCES folders (id) ON DELETE CASCADE
+    );
+    ALTER TABLE seeds ADD COLUMN folder_id INTEGER REFERENCES folders (id) ON DELETE SET NULL;
+    ",
+  )
+}
+
+/// 版本 7 增加了标签
+fn add_tags(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE tags (
+      id INTEGER PRIMARY KEY,
+      name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE article_tags (
+      article_id INTEGER NOT NULL REFERENCES articles (id) ON DELETE CASCADE,
+      tag_id INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+      PRIMARY KEY (article_id, tag_id)
+    );
+    CREATE INDEX article_tags_tag_id ON article_tags (tag_id);
+    ",
+  )
+}
+
+/// 版本 8 增加了全文内容和全文索引，索引由触发器与 articles 保持同步
+fn add_full_text(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE articles ADD COLUMN content TEXT;
+    CREATE VIRTUAL TABLE articles_fts USING fts5 (
+      title, author, desc, content,
+      content = 'articles', content_rowid = 'id', tokenize = 'unicode61 remove_diacritics 2'
+    );
+    CREATE TRIGGER articles_fts_insert AFTER INSERT ON articles BEGIN
+      INSERT INTO articles_fts (rowid, title, author, desc, content)
+        VALUES (new.id, new.title, new.author, new.desc, new.content);
+    END;
+    CREATE TRIGGER articles_fts_delete AFTER DELETE ON articles BEGIN
+      INSERT INTO articles_fts (articles_fts, rowid, title, author, desc, content)
+        VALUES ('delete', old.id, old.title, old.author, old.desc, old.content);
+    END;
+    CREATE TRIGGER articles_fts_update AFTER UPDATE OF title, author, desc, content ON articles BEGIN
+      INSERT INTO articles_fts (articles_fts, rowid, title, author, desc, content)
+        VALUES ('delete', old.id, old.title, old.author, old.desc, old.content);
+      INSERT INTO articles_fts (rowid, title, author, desc, content)
+        VALUES (new.id, new.title, new.author, new.desc, new.content);
+    END;
+    INSERT INTO articles_fts (articles_fts) VALUES ('rebuild');
+    ",
+  )
+}
+
+/// 版本 9 增加了保存的搜索
+fn add_saved_searches(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE saved_searches (
+      id INTEGER PRIMARY KEY,
+      name TEXT NOT NULL UNIQUE,
+      query TEXT NOT NULL
+    );
+    ",
+  )
+}
+
+/// 版本 10 增加了监视规则的匹配选项
+fn add_watch_options(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE watch_list ADD COLUMN match_mode TEXT NOT NULL DEFAULT 'substring';
+    ALTER TABLE watch_list ADD COLUMN case_sensitive INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE watch_list ADD COLUMN fields TEXT NOT NULL DEFAULT '[\"title\"]';
+    ALTER TABLE watch_list ADD COLUMN seed_id INTEGER REFERENCES seeds (id) ON DELETE CASCADE;
+    ALTER TABLE watch_list ADD COLUMN folder_id INTEGER REFERENCES folders (id) ON DELETE CASCADE;
+    ",
+  )
+}
+
+/// 版本 11 增加了种子的通知设置
+fn add_seed_notify(tx: &Transaction) -> Result<()> {
+  tx.execute_batch("ALTER TABLE seeds ADD COLUMN notify INTEGER;")
+}
+
+/// 版本 12 增加了规则
+fn add_rules(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE rules (
+      id INTEGER PRIMARY KEY,
+      name TEXT NOT NULL,
+      enabled INTEGER NOT NULL DEFAULT 1,
+      condition TEXT NOT NULL,
+      actions TEXT NOT NULL
+    );
+    ",
+  )
+}
+
+/// 版本 13 增加了静音规则
+fn add_mute_rules(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE mute_rules (
+      id INTEGER PRIMARY KEY,
+      keyword TEXT NOT NULL,
+      match_mode TEXT NOT NULL DEFAULT 'substring',
+      case_sensitive INTEGER NOT NULL DEFAULT 1,
+      fields TEXT NOT NULL DEFAULT '[\"title\"]',
+      seed_id INTEGER REFERENCES seeds (id) ON DELETE CASCADE,
+      folder_id INTEGER REFERENCES folders (id) ON DELETE CASCADE,
+      action TEXT NOT NULL DEFAULT 'hide',
+      muted_count INTEGER NOT NULL DEFAULT 0
+    );
+    ALTER TABLE articles ADD COLUMN muted_by INTEGER REFERENCES mute_rules (id) ON DELETE SET NULL;
+    ",
+  )
+}
+
+/// 版本 14 增加了种子最近的抓取错误和条件请求的校验信息
+fn add_fetch_status(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE seeds ADD COLUMN last_fetch_error TEXT;
+    ALTER TABLE seeds ADD COLUMN etag TEXT;
+    ALTER TABLE seeds ADD COLUMN last_modified TEXT;
+    ",
+  )
+}
+
+/// 版本 15 增加了种子的暂停状态
+fn add_seed_paused(tx: &Transaction) -> Result<()> {
+  tx.execute_batch("ALTER TABLE seeds ADD COLUMN paused INTEGER NOT NULL DEFAULT 0;")
+}
+
+/// 版本 16 增加了种子图标的更新时间
+fn add_favicon_updated_at(tx: &Transaction) -> Result<()> {
+  tx.execute_batch("ALTER TABLE seeds ADD COLUMN favicon_updated_at INTEGER NOT NULL DEFAULT 0;")
+}
+
+/// 版本 17 增加了种子的频道信息
+fn add_channel_info(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE seeds ADD COLUMN channel_title TEXT;
+    ALTER TABLE seeds ADD COLUMN channel_description TEXT;
+    ALTER TABLE seeds ADD COLUMN channel_link TEXT;
+    ALTER TABLE seeds ADD COLUMN channel_language TEXT;
+    ALTER TABLE seeds ADD COLUMN channel_image TEXT;
+    ALTER TABLE seeds ADD COLUMN channel_generator TEXT;
+    ALTER TABLE seeds ADD COLUMN channel_last_build_date INTEGER;
+    ",
+  )
+}
+
+/// 版本 18 增加了种子的正文提取选项
+fn add_extract_options(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE seeds ADD COLUMN auto_extract INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE seeds ADD COLUMN extract_selector TEXT;
+    ",
+  )
+}
+
+/// 版本 19 增加了图片缓存
+fn add_image_cache(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE article_images (
+      article_id INTEGER NOT NULL REFERENCES articles (id) ON DELETE CASCADE ON UPDATE CASCADE,
+      hash TEXT NOT NULL,
+      url TEXT NOT NULL,
+      PRIMARY KEY (article_id, hash)
+    );
+    CREATE INDEX article_images_hash ON article_images (hash);
+    CREATE TABLE image_cache (
+      hash TEXT PRIMARY KEY,
+      content_type TEXT,
+      size INTEGER NOT NULL,
+      accessed_at INTEGER NOT NULL
+    );
+    CREATE INDEX image_cache_accessed_at ON image_cache (accessed_at);
+    ",
+  )
+}
+
+/// 版本 20 清理已有文章的 HTML，原始内容保存在单独的列中
+fn add_raw_html(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE articles ADD COLUMN raw_desc TEXT;
+    ALTER TABLE articles ADD COLUMN raw_content TEXT;
+    UPDATE articles SET raw_desc = desc, raw_content = content;
+    ",
+  )?;
+
+  let articles = {
+    let mut stmt = tx.prepare("SELECT id, link, desc, content FROM articles")?;
+    let rows = stmt.query_map([], |row| {
+      Ok((
+        row.get::<_, i64>(0)?,
+        row.get::<_, Option<String>>(1)?,
+        row.get::<_, Option<String>>(2)?,
+        row.get::<_, Option<String>>(3)?,
+      ))
+    })?;
+    rows.collect::<Result<Vec<_>>>()?
+  };
+  let mut stmt = tx.prepare("UPDATE articles SET desc = ?2, content = ?3 WHERE id = ?1")?;
+
+  for (id, link, desc, content) in articles {
+    let base = link.and_then(|link| Url::parse(&link).ok());
+    let desc = desc.map(|html| sanitize_html(&html, base.as_ref()));
+    let content = content.map(|html| sanitize_html(&html, base.as_ref()));
+    stmt.execute(params![id, desc, content])?;
+  }
+
+  Ok(())
+}
+
+/// 版本 21 允许在不同的种子或文件夹中监视相同的关键字。
+///
+/// SQLite 不能删除列上的约束，因此重建 `watch_list`，改为在关键字和范围上建唯一索引。
+fn scope_watch_keywords(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    CREATE TABLE watch_list_new (
+      id INTEGER PRIMARY KEY,
+      keyword TEXT NOT NULL,
+      match_mode TEXT NOT NULL DEFAULT 'substring',
+      case_sensitive INTEGER NOT NULL DEFAULT 1,
+      fields TEXT NOT NULL DEFAULT '[\"title\"]',
+      seed_id INTEGER REFERENCES seeds (id) ON DELETE CASCADE,
+      folder_id INTEGER REFERENCES folders (id) ON DELETE CASCADE
+    );
+    INSERT INTO watch_list_new (id, keyword, match_mode, case_sensitive, fields, seed_id, folder_id)
+      SELECT id, keyword, match_mode, case_sensitive, fields, seed_id, folder_id FROM watch_list;
+    DROP TABLE watch_list;
+    ALTER TABLE watch_list_new RENAME TO watch_list;
+    CREATE UNIQUE INDEX watch_list_scope ON watch_list (keyword, IFNULL(seed_id, 0), IFNULL(folder_id, 0));
+    ",
+  )
+}
+
+/// 版本 22 记录图片下载失败的次数和时间，失败的图片过一段时间后重试
+fn add_image_failures(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE image_cache ADD COLUMN failures INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE image_cache ADD COLUMN failed_at INTEGER;
+    UPDATE image_cache SET failures = 1, failed_at = accessed_at WHERE content_type IS NULL;
+    ",
+  )
+}
+
+/// 版本 23 全文索引改为索引摘要和内容的纯文本，搜索和摘录不再包含 HTML 标签和属性。
+///
+/// 纯文本保存在 `desc_text` 和 `content_text` 列中，由写入文章的代码维护；
+/// 索引通过视图读取，列名与之前相同，搜索语法不变。
+fn index_plain_text(tx: &Transaction) -> Result<()> {
+  tx.execute_batch(
+    "
+    ALTER TABLE articles ADD COLUMN desc_text TEXT;
+    ALTER TABLE articles ADD COLUMN content_text TEXT;
+    DROP TRIGGER articles_fts_insert;
+    DROP TRIGGER articles_fts_delete;
+    DROP TRIGGER articles_fts_update;
+    DROP TABLE articles_fts;
+    ",
+  )?;
+
+  {
+    let articles = {
+      let mut stmt = tx.prepare("SELECT id, desc, content FROM articles")?;
+      let rows = stmt.query_map([], |row| {
+        Ok((
+          row.get::<_, i64>(0)?,
+          row.get::<_, Option<String>>(1)?,
+          row.get::<_, Option<String>>(2)?,
+        ))
+      })?;
+      rows.collect::<Result<Vec<_>>>()?
+    };
+    let mut stmt =
+      tx.prepare("UPDATE articles SET desc_text = ?2, content_text = ?3 WHERE id = ?1")?;
+
+    for (id, desc, content) in articles {
+      let desc = desc.as_deref().map(html_to_text);
+      let content = content.as_deref().map(html_to_text);
+      stmt.execute(params![id, desc, content])?;
+    }
+  }
+
+  tx.execute_batch(
+    "
+    CREATE VIEW articles_text AS
+      SELECT id, title, author, desc_text AS desc, content_text AS content FROM articles;
+    CREATE VIRTUAL TABLE articles_fts USING fts5 (
+      title, author, desc, content,
+      content = 'articles_text', content_rowid = 'id', tokenize = 'unicode61 remove_diacritics 2'
+    );
+    CREATE TRIGGER articles_fts_insert AFTER INSERT ON articles BEGIN
+      INSERT INTO articles_fts (rowid, title, author, desc, content)
+        VALUES (new.id, new.title, new.author, new.desc_text, new.content_text);
+    END;
+    CREATE TRIGGER articles_fts_delete AFTER DELETE ON articles BEGIN
+      INSERT INTO articles_fts (articles_fts, rowid, title, author, desc, content)
+        VALUES ('delete', old.id, old.title, old.author, old.desc_text, old.content_text);
+    END;
+    CREATE TRIGGER articles_fts_update AFTER UPDATE OF title, author, desc_text, content_text ON articles BEGIN
+      INSERT INTO articles_fts (articles_fts, rowid, title, author, desc, content)
+        VALUES ('delete', old.id, old.title, old.author, old.desc_text, old.content_text);
+      INSERT INTO articles_fts (rowid, title, author, desc, content)
+        VALUES (new.id, new.title, new.author, new.desc_text, new.content_text);
+    END;
+    INSERT INTO articles_fts (articles_fts) VALUES ('rebuild');
+    ",
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// 当前的数据库版本
+  const LATEST: u32 = 23;
+
+  /// 创建指定版本的数据库。版本 1 到 3 使用与版本 4 相同的建表语句，版本 0 为空数据库
+  fn open_at(version: u32) -> Connection {
+    let mut db = Connection::open_in_memory().unwrap();
+    db.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+    if version > 0 {
+      let tx = db.transaction().unwrap();
+      create_tables(&tx).unwrap();
+
+      for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > 4 && migration.version <= version)
+      {
+        (migration.up)(&tx).unwrap();
+      }
+
+      tx.pragma_update(None, "user_version", version).unwrap();
+      tx.commit().unwrap();
+    }
+
+    db
+  }
+
+  fn user_version(db: &Connection) -> u32 {
+    db.query_row("PRAGMA user_version", [], |row| row.get(0))
+      .unwrap()
+  }
+
+  /// 所有表、索引、触发器的定义
+  fn schema(db: &Connection) -> Vec<(String, String, Option<String>)> {
+    let mut stmt = db
+      .prepare("SELECT type, name, sql FROM sqlite_master ORDER BY type, name")
+      .unwrap();
+    let rows = stmt
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+      .unwrap();
+    rows.collect::<Result<_>>().unwrap()
+  }
+
+  fn columns(db: &Connection, table: &str) -> Vec<String> {
+    let mut stmt = db
+      .prepare("SELECT name FROM pragma_table_info(?1)")
+      .unwrap();
+    let rows = stmt.query_map([table], |row| row.get(0)).unwrap();
+    rows.collect::<Result<_>>().unwrap()
+  }
+
+  fn versions() -> impl Iterator<Item = u32> {
+    (0..4).chain(MIGRATIONS.iter().map(|migration| migration.version))
+  }
+
+  #[test]
+  fn latest_version() {
+    assert_eq!(MIGRATIONS.last().unwrap().version, LATEST);
+    assert!(MIGRATIONS
+      .windows(2)
+      .all(|pair| pair[0].version < pair[1].version));
+  }
+
+  #[test]
+  fn upgrade_from_every_version() {
+    let mut fresh = open_at(0);
+    upgrade_if_needed(&mut fresh, 0).unwrap();
+    let expected = schema(&fresh);
+
+    for version in versions() {
+      let mut db = open_at(version);
+      upgrade_if_needed(&mut db, version).unwrap();
+
+      assert_eq!(user_version(&db), LATEST, "from version {}", version);
+      assert_eq!(schema(&db), expected, "from version {}", version);
+    }
+  }
+
+  #[test]
+  fn final_schema() {
+    let mut db = open_at(0);
+    upgrade_if_needed(&mut db, 0).unwrap();
+    let articles = columns(&db, "articles");
+    let seeds = columns(&db, "seeds");
+
+    for column in [
+      "starred",
+      "content",
+      "muted_by",
+      "raw_desc",
+      "raw_content",
+      "desc_text",
+      "content_text",
+    ] {
+      assert!(articles.iter().any(|name| name == column), "{}", column);
+    }
+
+    for column in [
+      "folder_id",
+      "notify",
+      "last_fetch_error",
+      "etag",
+      "last_modified",
+      "paused",
+      "favicon_updated_at",
+      "channel_title",
+      "channel_last_build_date",
+      "auto_extract",
+      "extract_selector",
+    ] {
+      assert!(seeds.iter().any(|name| name == column), "{}", column);
+    }
+
+    for table in [
+      "folders",
+      "tags",
+      "article_tags",
+      "articles_fts",
+      "saved_searches",
+      "rules",
+      "mute_rules",
+      "article_images",
+      "image_cache",
+    ] {
+      assert!(!columns(&db, table).is_empty(), "{}", table);
+    }
+
+    assert_eq!(
+      columns(&db, "articles_fts"),
+      ["title", "author", "desc", "content"]
+    );
+
+    let mut stmt = db
+      .prepare("SELECT name FROM sqlite_master WHERE type = 'trigger' AND tbl_name = 'articles' ORDER BY name")
+      .unwrap();
+    let triggers: Vec<String> = stmt
+      .query_map([], |row| row.get(0))
+      .unwrap()
+      .collect::<Result<_>>()
+      .unwrap();
+    assert_eq!(
+      triggers,
+      [
+        "articles_fts_delete",
+        "articles_fts_insert",
+        "articles_fts_update"
+      ]
+    );
+  }
+
+  #[test]
+  fn upgrade_keeps_articles() {
+    for version in versions().filter(|version| (1..20).contains(version)) {
+      let mut db = open_at(version);
+      db.execute_batch(
+        r#"
+        INSERT INTO seeds (id, name, url) VALUES (1, 'seed', 'https://example.com/feed');
+        INSERT INTO articles (id, seed_id, guid, title, desc, link, pub_date, unread)
+          VALUES (1, 1, 'guid', 'Hello', '<p onclick="x()">world<script>evil()</script><img src="a.png"></p>', 'https://example.com/posts/1', 0, 1);
+        "#,
+      )
+      .unwrap();
+      upgrade_if_needed(&mut db, version).unwrap();
+
+      let (desc, raw_desc, raw_content): (String, String, Option<String>) = db
+        .query_row(
+          "SELECT desc, raw_desc, raw_content FROM articles WHERE id = 1",
+          [],
+          |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+
+      // 版本 20 之前保存的 HTML 在升级时清理，原始内容保留
+      assert_eq!(
+        desc, r#"<p>world<img src="https://example.com/posts/a.png"></p>"#,
+        "from version {}",
+        version
+      );
+      assert!(raw_desc.contains("<script>"), "from version {}", version);
+      assert_eq!(raw_content, None);
+
+      // 全文索引跟随清理后的内容
+      let count = |query: &str| -> i64 {
+        db.query_row(
+          "SELECT COUNT(*) FROM articles_fts WHERE articles_fts MATCH ?1",
+          [query],
+          |row| row.get(0),
+        )
+        .unwrap()
+      };
+      assert_eq!(count("hello"), 1, "from version {}", version);
+      assert_eq!(count("evil"), 0, "from version {}", version);
+      // 只索引纯文本，标签和属性中的 URL 不会被搜索到
+      assert_eq!(count("png"), 0, "from version {}", version);
+
+      let snippet: String = db
+        .query_row(
+          "SELECT snippet(articles_fts, 2, '[', ']', '…', 8) FROM articles_fts WHERE articles_fts MATCH 'world'",
+          [],
+          |row| row.get(0),
+        )
+        .unwrap();
+      assert_eq!(snippet, "[world]", "from version {}", version);
+    }
+  }
+
+  #[test]
+  fn watch_keywords_are_unique_per_scope() {
+    let mut db = open_at(20);
+    db.execute_batch(
+      "
+      INSERT INTO seeds (id, name, url) VALUES (1, 'Seed', 'https://example.com/feed');
+      INSERT INTO watch_list (id, keyword, seed_id) VALUES (7, 'rust', NULL);
+      ",
+    )
+    .unwrap();
+    upgrade_if_needed(&mut db, 20).unwrap();
+
+    let keyword: String = db
+      .query_row("SELECT keyword FROM watch_list WHERE id = 7", [], |row| {
+        row.get(0)
+      })
+      .unwrap();
+    assert_eq!(keyword, "rust");
+
+    db.execute(
+      "INSERT INTO watch_list (keyword, seed_id) VALUES ('rust', 1)",
+      [],
+    )
+    .unwrap();
+    assert!(db
+      .execute("INSERT INTO watch_list (keyword) VALUES ('rust')", [])
+      .is_err());
+    assert!(db
+      .execute(
+        "INSERT INTO watch_list (keyword, seed_id) VALUES ('rust', 1)",
+        []
+      )
+      .is_err());
+  }
+
+  #[test]
+  fn image_failures_keep_time() {
+    let mut db = open_at(21);
+    db.execute_batch(
+      "
+      INSERT INTO image_cache (hash, content_type, size, accessed_at) VALUES ('a', 'image/png', 3, 10);
+      INSERT INTO image_cache (hash, content_type, size, accessed_at) VALUES ('b', NULL, 0, 20);
+      ",
+    )
+    .unwrap();
+    upgrade_if_needed(&mut db, 21).unwrap();
+
+    let mut stmt = db
+      .prepare("SELECT hash, failures, failed_at FROM image_cache ORDER BY hash")
+      .unwrap();
+    let rows: Vec<(String, i64, Option<i64>)> = stmt
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+      .unwrap()
+      .collect::<Result<_>>()
+      .unwrap();
+    assert_eq!(
+      rows,
+      [("a".to_string(), 0, None), ("b".to_string(), 1, Some(20))]
+    );
+  }
+
+  #[test]
+  fn upgrade_is_idempotent() {
+    let mut db = open_at(0);
+    upgrade_if_needed(&mut db, 0).unwrap();
+    let expected = schema(&db);
+
+    upgrade_if_needed(&mut db, LATEST).unwrap();
+    assert_eq!(schema(&db), expected);
+    assert_eq!(user_version(&db), LATEST);
+  }
+
+  #[test]
+  fn failed_migration_rolls_back() {
+    let mut db = open_at(12);
+    // 与版本 13 冲突的表
+    db.execute_batch("CREATE TABLE mute_rules (id INTEGER PRIMARY KEY);")
+      .unwrap();
+
+    assert!(upgrade_if_needed(&mut db, 12).is_err());
+    assert_eq!(user_version(&db), 12);
+    assert!(!columns(&db, "articles")
+      .iter()
+      .any(|name| name == "muted_by"));
+  }
+}
@@ -0,0 +1,84 @@
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::watch::{Candidate, Matcher};
+
+/// 静音方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum MuteAction {
+  /// 隐藏，只在查看已静音的文章时显示
+  #[default]
+  Hide,
+  /// 标记为已读
+  MarkRead,
+}
+
+impl MuteAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Hide => "hide",
+      Self::MarkRead => "markRead",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "markRead" => Self::MarkRead,
+      _ => Self::Hide,
+    }
+  }
+}
+
+/// 静音规则
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MuteRule {
+  /// ID，新建时为 0
+  #[serde(default)]
+  pub id: i64,
+  /// 静音方式
+  #[serde(default)]
+  pub action: MuteAction,
+  /// 已静音的文章数量，保存时忽略
+  #[serde(default)]
+  pub muted_count: i64,
+  /// 匹配条件
+  #[serde(flatten)]
+  pub matcher: Matcher,
+}
+
+/// 静音过滤器，在插入新文章之前检查
+#[derive(Debug, Default)]
+pub struct MuteFilter {
+  rules: Vec<(MuteRule, Regex)>,
+}
+
+impl MuteFilter {
+  /// 编译规则，跳过无效的规则
+  pub fn new(rules: Vec<MuteRule>) -> Self {
+    let rules = rules
+      .into_iter()
+      .filter_map(|rule| match rule.matcher.to_regex() {
+        Ok(regex) => Some((rule, regex)),
+        Err(err) => {
+          warn!("Invalid mute rule {}: {:?}", &rule.matcher.keyword, err);
+          None
+        }
+      })
+      .collect();
+
+    Self { rules }
+  }
+
+  /// 返回第一个匹配的规则的 ID 及其静音方式
+  pub fn check(&self, candidate: &Candidate) -> Option<(i64, MuteAction)> {
+    self
+      .rules
+      .iter()
+      .find(|(rule, regex)| rule.matcher.is_match(regex, candidate))
+      .map(|(rule, _)| (rule.id, rule.action))
+  }
+}
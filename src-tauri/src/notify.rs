@@ -0,0 +1,171 @@
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::Result;
+use chrono::{Local, NaiveTime, Timelike};
+use log::warn;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{api::notification::Notification, AppHandle, Manager};
+
+use crate::{
+  db::{get_watched_ids, DbAccess},
+  seed::Seed,
+};
+
+/// 一次抓取中逐条通知的文章数量上限，超出的合并为一条
+const MAX_NOTIFICATIONS: usize = 3;
+
+/// 最近一次通知的文章，用户在主窗口中查看。新的通知会替换之前的文章
+static NOTIFIED_ARTICLE: Mutex<Option<i64>> = Mutex::new(None);
+
+/// 新插入的文章
+#[derive(Debug)]
+pub struct NewArticle {
+  /// ID
+  pub id: i64,
+  /// 标题
+  pub title: Option<String>,
+  /// 是否由规则要求通知
+  pub notify: bool,
+}
+
+/// 通知设置
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+  /// 是否启用通知
+  pub enabled: bool,
+  /// 是否通知监视列表命中的文章
+  pub watch_list: bool,
+  /// 是否通知所有新文章
+  pub new_articles: bool,
+  /// 免打扰开始时间，当天的分钟数
+  pub quiet_start: Option<u32>,
+  /// 免打扰结束时间，当天的分钟数
+  pub quiet_end: Option<u32>,
+}
+
+impl Default for NotificationSettings {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      watch_list: true,
+      new_articles: false,
+      quiet_start: None,
+      quiet_end: None,
+    }
+  }
+}
+
+impl NotificationSettings {
+  /// 是否处于免打扰时段，时段可以跨越午夜
+  pub fn is_quiet(&self, now: NaiveTime) -> bool {
+    if let (Some(start), Some(end)) = (self.quiet_start, self.quiet_end) {
+      let now = now.hour() * 60 + now.minute();
+
+      if start <= end {
+        start <= now && now < end
+      } else {
+        now >= start || now < end
+      }
+    } else {
+      false
+    }
+  }
+
+  /// 当前是否允许通知
+  pub fn can_notify(&self, now: NaiveTime) -> bool {
+    self.enabled && !self.is_quiet(now)
+  }
+
+  /// 是否应该通知。`seed_notify` 为种子的通知设置，`None` 表示跟随全局设置。
+  pub fn should_notify(&self, seed_notify: Option<bool>, watched: bool, now: NaiveTime) -> bool {
+    if !self.can_notify(now) {
+      return false;
+    }
+
+    match seed_notify {
+      Some(notify) => notify,
+      None => self.new_articles || (watched && self.watch_list),
+    }
+  }
+}
+
+/// 获取通知设置
+pub fn get_notification_settings(db: &Connection) -> Result<NotificationSettings> {
+  let mut stmt = db.prepare("SELECT value FROM settings WHERE key = ?1")?;
+  let mut rows = stmt.query(["notification"])?;
+
+  if let Some(row) = rows.next()? {
+    let value: String = row.get("value")?;
+    let settings: NotificationSettings = serde_json::from_str(value.as_str())?;
+    Ok(settings)
+  } else {
+    Ok(NotificationSettings::default())
+  }
+}
+
+fn show(app_handle: &AppHandle, title: &str, body: &str) {
+  let identifier = app_handle.config().tauri.bundle.identifier.clone();
+  let result = Notification::new(identifier).title(title).body(body).show();
+
+  if let Err(err) = result {
+    warn!("Failed to show notification: {:?}", err);
+  }
+}
+
+/// 为新插入的文章发送桌面通知，在插入文章的事务提交后调用，只使用只读连接
+pub async fn notify_new_articles(
+  app_handle: &AppHandle,
+  seed: &Seed,
+  articles: &[NewArticle],
+) -> Result<()> {
+  if articles.is_empty() {
+    return Ok(());
+  }
+
+  let ids: Vec<i64> = articles.iter().map(|article| article.id).collect();
+  let (settings, watched) = app_handle
+    .db(move |db| -> Result<_> { Ok((get_notification_settings(db)?, get_watched_ids(db, &ids)?)) })
+    .await?;
+  let now = Local::now().time();
+  let notified: Vec<&NewArticle> = articles
+    .iter()
+    .filter(|article| {
+      (article.notify && settings.can_notify(now))
+        || settings.should_notify(seed.notify, watched.contains(&article.id), now)
+    })
+    .collect();
+
+  if notified.is_empty() {
+    return Ok(());
+  }
+
+  for article in notified.iter().take(MAX_NOTIFICATIONS) {
+    show(
+      app_handle,
+      &seed.name,
+      article.title.as_deref().unwrap_or_default(),
+    );
+  }
+
+  if notified.len() > MAX_NOTIFICATIONS {
+    let body = format!("{} more new articles", notified.len() - MAX_NOTIFICATIONS);
+    show(app_handle, &seed.name, &body);
+  }
+
+  *NOTIFIED_ARTICLE
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner) = Some(notified[0].id);
+
+  Ok(())
+}
+
+/// 获取最近一次通知的文章 ID，没有通知过时返回 `None`。
+#[tauri::command]
+#[specta::specta]
+pub fn get_notified_article() -> Option<i64> {
+  *NOTIFIED_ARTICLE
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,63 @@
+use std::sync::{
+  atomic::{AtomicUsize, Ordering},
+  Mutex, MutexGuard, PoisonError, TryLockError,
+};
+
+use rusqlite::Connection;
+
+/// 数据库连接池。
+///
+/// 只有一个写连接，所有写操作依次执行；只读连接有多个，在 WAL 模式下读取不会被写入（包括 `VACUUM`）阻塞。
+pub struct DbPool {
+  writer: Mutex<Connection>,
+  readers: Vec<Mutex<Connection>>,
+  next_reader: AtomicUsize,
+}
+
+/// 获取锁，忽略持有锁的线程 panic 造成的中毒，连接本身仍然可用
+fn lock(mutex: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+  mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl DbPool {
+  /// 创建连接池，`readers` 不能为空
+  pub fn new(writer: Connection, readers: Vec<Connection>) -> Self {
+    assert!(
+      !readers.is_empty(),
+      "The pool should have at least one reader."
+    );
+
+    Self {
+      writer: Mutex::new(writer),
+      readers: readers.into_iter().map(Mutex::new).collect(),
+      next_reader: AtomicUsize::new(0),
+    }
+  }
+
+  /// 使用只读连接，优先使用空闲的连接，都在使用中时轮流等待
+  pub fn read<F, TResult>(&self, operation: F) -> TResult
+  where
+    F: FnOnce(&Connection) -> TResult,
+  {
+    for reader in &self.readers {
+      match reader.try_lock() {
+        Ok(db) => return operation(&db),
+        Err(TryLockError::Poisoned(err)) => return operation(&err.into_inner()),
+        Err(TryLockError::WouldBlock) => {}
+      }
+    }
+
+    let index = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+    let db = lock(&self.readers[index]);
+    operation(&db)
+  }
+
+  /// 使用写连接
+  pub fn write<F, TResult>(&self, operation: F) -> TResult
+  where
+    F: FnOnce(&mut Connection) -> TResult,
+  {
+    let mut db = lock(&self.writer);
+    operation(&mut db)
+  }
+}
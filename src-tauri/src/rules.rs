@@ -0,0 +1,250 @@
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::watch::{Candidate, Matcher};
+
+/// 规则的动作
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum RuleAction {
+  /// 标记为已读
+  MarkRead,
+  /// 加星标
+  Star,
+  /// 添加标签
+  Tag { tag: String },
+  /// 不插入
+  Skip,
+  /// 发送桌面通知
+  Notify,
+}
+
+/// 规则
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+  /// ID，新建时为 0
+  #[serde(default)]
+  pub id: i64,
+  /// 名称
+  pub name: String,
+  /// 是否启用
+  pub enabled: bool,
+  /// 匹配条件
+  pub condition: Matcher,
+  /// 匹配时执行的动作
+  pub actions: Vec<RuleAction>,
+}
+
+/// 规则对一篇文章的处理结果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RuleOutcome {
+  /// 不插入
+  pub skip: bool,
+  /// 标记为已读
+  pub mark_read: bool,
+  /// 加星标
+  pub star: bool,
+  /// 发送桌面通知
+  pub notify: bool,
+  /// 添加的标签
+  pub tags: Vec<String>,
+}
+
+/// 规则引擎，在插入新文章之前对其求值
+#[derive(Debug, Default)]
+pub struct RuleEngine {
+  rules: Vec<(Rule, Regex)>,
+}
+
+impl RuleEngine {
+  /// 编译规则，跳过禁用的和无效的规则
+  pub fn new(rules: Vec<Rule>) -> Self {
+    let rules = rules
+      .into_iter()
+      .filter(|rule| rule.enabled)
+      .filter_map(|rule| match rule.condition.to_regex() {
+        Ok(regex) => Some((rule, regex)),
+        Err(err) => {
+          warn!("Invalid rule {}: {:?}", &rule.name, err);
+          None
+        }
+      })
+      .collect();
+
+    Self { rules }
+  }
+
+  /// 对文章依次应用所有匹配的规则
+  pub fn evaluate(&self, candidate: &Candidate) -> RuleOutcome {
+    let mut outcome = RuleOutcome::default();
+
+    for (rule, regex) in &self.rules {
+      if !rule.condition.is_match(regex, candidate) {
+        continue;
+      }
+
+      for action in &rule.actions {
+        match action {
+          RuleAction::MarkRead => outcome.mark_read = true,
+          RuleAction::Star => outcome.star = true,
+          RuleAction::Tag { tag } => {
+            if !outcome.tags.contains(tag) {
+              outcome.tags.push(tag.clone());
+            }
+          }
+          RuleAction::Skip => outcome.skip = true,
+          RuleAction::Notify => outcome.notify = true,
+        }
+      }
+    }
+
+    outcome
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::watch::{MatchField, MatchMode};
+
+  fn rule(name: &str, keyword: &str, actions: Vec<RuleAction>) -> Rule {
+    Rule {
+      id: 0,
+      name: name.to_string(),
+      enabled: true,
+      condition: Matcher {
+        keyword: keyword.to_string(),
+        match_mode: MatchMode::Substring,
+        case_sensitive: false,
+        fields: vec![MatchField::Title],
+        seed_id: None,
+        folder_id: None,
+      },
+      actions,
+    }
+  }
+
+  fn candidate<'a>(seed_id: i64, folder_ids: &'a [i64], title: &'a str) -> Candidate<'a> {
+    Candidate {
+      seed_id,
+      folder_ids,
+      title: Some(title),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn each_action() {
+    let engine = RuleEngine::new(vec![
+      rule("read", "read", vec![RuleAction::MarkRead]),
+      rule("star", "star", vec![RuleAction::Star]),
+      rule(
+        "tag",
+        "tag",
+        vec![RuleAction::Tag {
+          tag: String::from("news"),
+        }],
+      ),
+      rule("skip", "skip", vec![RuleAction::Skip]),
+      rule("notify", "notify", vec![RuleAction::Notify]),
+    ]);
+
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Read me")),
+      RuleOutcome {
+        mark_read: true,
+        ..Default::default()
+      }
+    );
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Star me")),
+      RuleOutcome {
+        star: true,
+        ..Default::default()
+      }
+    );
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Tag me")),
+      RuleOutcome {
+        tags: vec![String::from("news")],
+        ..Default::default()
+      }
+    );
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Skip me")),
+      RuleOutcome {
+        skip: true,
+        ..Default::default()
+      }
+    );
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Notify me")),
+      RuleOutcome {
+        notify: true,
+        ..Default::default()
+      }
+    );
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Nothing")),
+      RuleOutcome::default()
+    );
+  }
+
+  #[test]
+  fn seed_and_folder_scope() {
+    let mut by_seed = rule("seed", "rust", vec![RuleAction::Star]);
+    by_seed.condition.seed_id = Some(1);
+    let mut by_folder = rule("folder", "rust", vec![RuleAction::MarkRead]);
+    by_folder.condition.folder_id = Some(10);
+    let engine = RuleEngine::new(vec![by_seed, by_folder]);
+
+    let outcome = engine.evaluate(&candidate(1, &[], "Rust"));
+    assert!(outcome.star && !outcome.mark_read);
+
+    // 上级文件夹也算在范围内
+    let outcome = engine.evaluate(&candidate(2, &[20, 10], "Rust"));
+    assert!(!outcome.star && outcome.mark_read);
+
+    let outcome = engine.evaluate(&candidate(2, &[20], "Rust"));
+    assert_eq!(outcome, RuleOutcome::default());
+  }
+
+  #[test]
+  fn disabled_and_invalid_rules() {
+    let mut disabled = rule("disabled", "rust", vec![RuleAction::Skip]);
+    disabled.enabled = false;
+    let mut invalid = rule("invalid", "(", vec![RuleAction::Star]);
+    invalid.condition.match_mode = MatchMode::Regex;
+    let engine = RuleEngine::new(vec![disabled, invalid]);
+
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Rust (")),
+      RuleOutcome::default()
+    );
+  }
+
+  #[test]
+  fn multiple_rules() {
+    let tag = |tag: &str| RuleAction::Tag {
+      tag: tag.to_string(),
+    };
+    let engine = RuleEngine::new(vec![
+      rule("first", "rust", vec![RuleAction::MarkRead, tag("lang")]),
+      rule("second", "release", vec![RuleAction::Notify, tag("lang")]),
+      rule("third", "rust", vec![tag("news")]),
+    ]);
+
+    assert_eq!(
+      engine.evaluate(&candidate(1, &[], "Rust release")),
+      RuleOutcome {
+        mark_read: true,
+        notify: true,
+        tags: vec![String::from("lang"), String::from("news")],
+        ..Default::default()
+      }
+    );
+  }
+}
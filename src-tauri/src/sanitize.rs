@@ -0,0 +1,403 @@
+use kuchikiki::{parse_html, traits::TendrilSink, NodeData, NodeRef};
+use reqwest::Url;
+
+/// 允许的元素，其他元素只保留内容
+const ALLOWED_TAGS: &[&str] = &[
+  "a",
+  "abbr",
+  "audio",
+  "b",
+  "blockquote",
+  "br",
+  "caption",
+  "cite",
+  "code",
+  "col",
+  "colgroup",
+  "dd",
+  "del",
+  "details",
+  "dfn",
+  "div",
+  "dl",
+  "dt",
+  "em",
+  "figcaption",
+  "figure",
+  "h1",
+  "h2",
+  "h3",
+  "h4",
+  "h5",
+  "h6",
+  "hr",
+  "i",
+  "img",
+  "ins",
+  "kbd",
+  "li",
+  "mark",
+  "ol",
+  "p",
+  "picture",
+  "pre",
+  "q",
+  "s",
+  "samp",
+  "small",
+  "source",
+  "span",
+  "strong",
+  "sub",
+  "summary",
+  "sup",
+  "table",
+  "tbody",
+  "td",
+  "tfoot",
+  "th",
+  "thead",
+  "time",
+  "tr",
+  "u",
+  "ul",
+  "video",
+];
+
+/// 连同内容一起删除的元素
+const DROPPED_TAGS: &[&str] = &[
+  "base", "button", "embed", "form", "frame", "frameset", "head", "iframe", "input", "link",
+  "math", "meta", "noscript", "object", "script", "select", "style", "svg", "template", "textarea",
+  "title",
+];
+
+/// 提取纯文本时前后加空格的块级元素，避免相邻段落的词连在一起
+const BLOCK_TAGS: &[&str] = &[
+  "blockquote",
+  "br",
+  "caption",
+  "dd",
+  "div",
+  "dt",
+  "figcaption",
+  "h1",
+  "h2",
+  "h3",
+  "h4",
+  "h5",
+  "h6",
+  "hr",
+  "li",
+  "p",
+  "pre",
+  "td",
+  "th",
+  "tr",
+];
+
+/// 允许的属性，其他属性（包括 `style` 和所有事件处理器）都会被删除。
+/// `data-link` 用于在文章列表中识别广告图片
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+  "alt",
+  "cite",
+  "colspan",
+  "controls",
+  "data-link",
+  "datetime",
+  "height",
+  "href",
+  "lang",
+  "open",
+  "poster",
+  "rowspan",
+  "span",
+  "src",
+  "srcset",
+  "title",
+  "type",
+  "width",
+];
+
+/// 值为 URL 的属性
+const URL_ATTRIBUTES: &[&str] = &["cite", "href", "poster", "src"];
+
+/// 允许的 URL 协议
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// 可以使用内嵌图片的元素，只限于 `src` 属性
+const DATA_URL_TAGS: &[&str] = &["img", "source"];
+
+/// 是否为允许内嵌的图片。SVG 可以包含脚本，不允许内嵌
+fn is_data_image(url: &Url) -> bool {
+  let mime = url
+    .path()
+    .split([';', ','])
+    .next()
+    .unwrap_or_default()
+    .trim()
+    .to_ascii_lowercase();
+
+  mime.starts_with("image/") && mime != "image/svg+xml"
+}
+
+/// 解析 URL，相对 URL 以 `base` 为基准。协议不在允许范围内时返回 `None`，
+/// 没有 `base` 时相对 URL 原样保留。`allow_data` 为是否允许内嵌的图片。
+fn resolve_url(value: &str, base: Option<&Url>, allow_data: bool) -> Option<String> {
+  let value = value.trim();
+  let url = match base {
+    Some(base) => base.join(value),
+    None => Url::parse(value),
+  };
+
+  match url {
+    Ok(url) if ALLOWED_SCHEMES.contains(&url.scheme()) => Some(url.to_string()),
+    // 内嵌的图片
+    Ok(url) if allow_data && url.scheme() == "data" && is_data_image(&url) => Some(url.to_string()),
+    Ok(_) => None,
+    // 没有协议的相对 URL
+    Err(_) if base.is_none() && !has_scheme(value) => Some(value.to_string()),
+    Err(_) => None,
+  }
+}
+
+/// 第一个 `/`、`?` 或 `#` 之前有 `:` 时视为带有协议
+fn has_scheme(value: &str) -> bool {
+  value
+    .split(['/', '?', '#'])
+    .next()
+    .is_some_and(|head| head.contains(':'))
+}
+
+/// 解析 `srcset`，逐个解析其中的 URL，删除无效的候选项
+fn resolve_srcset(value: &str, base: Option<&Url>) -> Option<String> {
+  let candidates: Vec<_> = value
+    .split(',')
+    .filter_map(|candidate| {
+      let mut parts = candidate.split_whitespace();
+      let url = resolve_url(parts.next()?, base, false)?;
+      let descriptor: Vec<_> = parts.collect();
+
+      if descriptor.is_empty() {
+        Some(url)
+      } else {
+        Some(format!("{} {}", url, descriptor.join(" ")))
+      }
+    })
+    .collect();
+
+  (!candidates.is_empty()).then(|| candidates.join(", "))
+}
+
+/// 清理元素的属性
+fn sanitize_attributes(node: &NodeRef, base: Option<&Url>) {
+  let Some(element) = node.as_element() else {
+    return;
+  };
+  let allow_data = DATA_URL_TAGS.contains(&&*element.name.local);
+  let mut attributes = element.attributes.borrow_mut();
+
+  attributes.map.retain(|name, attribute| {
+    let name = &*name.local;
+
+    if !ALLOWED_ATTRIBUTES.contains(&name) {
+      return false;
+    }
+
+    let value = if URL_ATTRIBUTES.contains(&name) {
+      resolve_url(&attribute.value, base, allow_data && name == "src")
+    } else if name == "srcset" {
+      resolve_srcset(&attribute.value, base)
+    } else {
+      return true;
+    };
+
+    match value {
+      Some(value) => {
+        attribute.value = value;
+        true
+      }
+      None => false,
+    }
+  });
+}
+
+/// 删除节点，保留其子节点
+fn unwrap(node: &NodeRef) {
+  for child in node.children().collect::<Vec<_>>() {
+    node.insert_before(child);
+  }
+
+  node.detach();
+}
+
+/// 按允许列表清理节点的所有后代
+pub fn sanitize_node(root: &NodeRef, base: Option<&Url>) {
+  for node in root.descendants().collect::<Vec<_>>() {
+    match node.data() {
+      NodeData::Element(element) => {
+        let name = &*element.name.local;
+
+        if DROPPED_TAGS.contains(&name) {
+          node.detach();
+        } else if ALLOWED_TAGS.contains(&name) {
+          sanitize_attributes(&node, base);
+        } else {
+          unwrap(&node);
+        }
+      }
+      NodeData::Text(_) => {}
+      _ => node.detach(),
+    }
+  }
+}
+
+/// 序列化节点的所有子节点
+pub fn inner_html(node: &NodeRef) -> String {
+  node.children().map(|child| child.to_string()).collect()
+}
+
+/// 按允许列表清理 HTML 片段：删除脚本、框架、表单等元素和所有事件处理器，
+/// 只保留安全的 URL 协议，并以 `base` 为基准解析相对 URL。
+pub fn sanitize_html(html: &str, base: Option<&Url>) -> String {
+  let document = parse_html().one(html);
+
+  match document.select_first("body") {
+    Ok(body) => {
+      let body = body.as_node();
+      sanitize_node(body, base);
+      inner_html(body)
+    }
+    Err(()) => String::new(),
+  }
+}
+
+/// 收集节点中的文本，块级元素前后加空格
+fn collect_text(node: &NodeRef, text: &mut String) {
+  for child in node.children() {
+    match child.data() {
+      NodeData::Text(value) => text.push_str(&value.borrow()),
+      NodeData::Element(element) => {
+        let block = BLOCK_TAGS.contains(&&*element.name.local);
+
+        if block {
+          text.push(' ');
+        }
+
+        collect_text(&child, text);
+
+        if block {
+          text.push(' ');
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+/// 提取 HTML 片段的纯文本，用于全文索引。连续的空白合并为一个空格
+pub fn html_to_text(html: &str) -> String {
+  let document = parse_html().one(html);
+  let mut text = String::new();
+
+  if let Ok(body) = document.select_first("body") {
+    collect_text(body.as_node(), &mut text);
+  }
+
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sanitize(html: &str) -> String {
+    let base = Url::parse("https://example.com/posts/1").unwrap();
+    sanitize_html(html, Some(&base))
+  }
+
+  #[test]
+  fn scripts_and_event_handlers() {
+    assert_eq!(
+      sanitize(r#"<p onclick="evil()" style="color: red">Hello<script>evil()</script></p>"#),
+      "<p>Hello</p>"
+    );
+    assert_eq!(
+      sanitize(r#"<img src="a.png" onerror="evil()"><style>p {}</style>"#),
+      r#"<img src="https://example.com/posts/a.png">"#
+    );
+  }
+
+  #[test]
+  fn unsafe_urls() {
+    assert_eq!(
+      sanitize(r#"<a href="javascript:evil()">link</a>"#),
+      "<a>link</a>"
+    );
+    assert_eq!(
+      sanitize(r#"<a href=" JavaScript:evil()">link</a>"#),
+      "<a>link</a>"
+    );
+    assert_eq!(
+      sanitize(r#"<a href="data:text/html,<script>evil()</script>">link</a>"#),
+      "<a>link</a>"
+    );
+    // 内嵌的图片只允许出现在图片的 `src` 中
+    assert_eq!(
+      sanitize(r#"<a href="data:image/png;base64,AAAA">link</a>"#),
+      "<a>link</a>"
+    );
+    assert_eq!(
+      sanitize(r#"<img src="data:image/png;base64,AAAA">"#),
+      r#"<img src="data:image/png;base64,AAAA">"#
+    );
+    assert_eq!(
+      sanitize(r#"<img src="data:image/svg+xml;base64,AAAA">"#),
+      "<img>"
+    );
+    assert_eq!(
+      sanitize(r#"<video poster="data:image/png;base64,AAAA"></video>"#),
+      "<video></video>"
+    );
+  }
+
+  #[test]
+  fn relative_urls() {
+    assert_eq!(
+      sanitize(
+        r#"<a href="../about">about</a><img src="/a.png" srcset="b.png 2x, javascript:x 3x">"#
+      ),
+      r#"<a href="https://example.com/about">about</a><img src="https://example.com/a.png" srcset="https://example.com/posts/b.png 2x">"#
+    );
+    assert_eq!(
+      sanitize_html(r#"<a href="../about">about</a>"#, None),
+      r#"<a href="../about">about</a>"#
+    );
+    assert_eq!(
+      sanitize_html(r#"<a href="javascript:evil()">link</a>"#, None),
+      "<a>link</a>"
+    );
+  }
+
+  #[test]
+  fn disallowed_tags() {
+    assert_eq!(
+      sanitize(r#"<form action="/x"><input name="a">Form</form><font color="red">Text</font>"#),
+      "Text"
+    );
+    assert_eq!(
+      sanitize(
+        r#"<iframe src="https://example.com"></iframe><!-- comment --><svg><a href="x">y</a></svg>"#
+      ),
+      ""
+    );
+  }
+
+  #[test]
+  fn plain_text() {
+    assert_eq!(
+      html_to_text("<p>Hello <b>wor</b>ld</p><p>Next\n  line<br>end</p>"),
+      "Hello world Next line end"
+    );
+    assert_eq!(html_to_text(r#"<img src="a.png" alt="image">"#), "");
+  }
+}
@@ -0,0 +1,166 @@
+use std::{
+  cmp::Reverse,
+  collections::{hash_map::RandomState, BinaryHeap, HashMap},
+  hash::BuildHasher,
+  sync::OnceLock,
+  time::Duration,
+};
+
+use chrono::Local;
+use log::{debug, error, info, warn};
+use tauri::AppHandle;
+use tokio::{sync::Notify, time::timeout};
+
+use crate::{
+  db::{get_all_seeds, DbAccess},
+  job::{fetch_scheduled, host, probe_network, ScheduledFetch},
+  seed::Seed,
+};
+
+/// 随机延迟的上限，秒
+const MAX_JITTER: i64 = 300;
+/// 最长等待时间。系统休眠期间计时器可能停止，定期醒来重新计算，避免错过抓取
+const MAX_WAIT: Duration = Duration::from_secs(300);
+/// 网络不可用时的最短等待时间
+const MIN_OFFLINE_BACKOFF: Duration = Duration::from_secs(15);
+/// 网络不可用时的最长等待时间
+const MAX_OFFLINE_BACKOFF: Duration = Duration::from_secs(120);
+/// 抓取失败或手动刷新正在进行时，稍后重试
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+/// 网络不可用时探测网络的间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 种子变化时唤醒调度器
+fn wake() -> &'static Notify {
+  static WAKE: OnceLock<Notify> = OnceLock::new();
+  WAKE.get_or_init(Notify::new)
+}
+
+/// 种子添加、修改、暂停或抓取后调用，调度器会重新计算抓取时间
+pub fn reschedule() {
+  wake().notify_one();
+}
+
+/// 种子的随机延迟，秒。
+///
+/// 同一站点的种子往往同时添加、更新周期相同，按站点错开请求：站点的主机名决定起点，
+/// 站点内的第 `index` 个种子（共 `count` 个）在延迟范围内均匀排开。重新计算抓取时间时保持不变。
+fn jitter(seed: &Seed, index: usize, count: usize) -> i64 {
+  static STATE: OnceLock<RandomState> = OnceLock::new();
+  let limit = (i64::from(seed.interval) * 60 / 10).clamp(1, MAX_JITTER);
+  let start = STATE.get_or_init(RandomState::new).hash_one(site(seed)) % limit as u64;
+  let offset = index as u64 * limit as u64 / count.max(1) as u64;
+
+  ((start + offset) % limit as u64) as i64
+}
+
+/// 种子所在的站点，无法解析 URL 时使用 URL 本身
+fn site(seed: &Seed) -> String {
+  host(&seed.url).unwrap_or_else(|| seed.url.clone())
+}
+
+/// 按下次抓取时间排列的优先队列，队首是最早要抓取的种子
+fn build_queue(seeds: &[Seed]) -> BinaryHeap<Reverse<(i64, i64)>> {
+  let mut scheduled: Vec<_> = seeds
+    .iter()
+    .filter_map(|seed| seed.next_fetch_at().map(|at| (at, seed)))
+    .collect();
+  scheduled.sort_by_key(|(_, seed)| seed.id);
+
+  // 按站点分组，组内按 ID 排序
+  let mut sites: HashMap<String, Vec<i64>> = HashMap::new();
+
+  for (_, seed) in &scheduled {
+    sites.entry(site(seed)).or_default().push(seed.id);
+  }
+
+  scheduled
+    .into_iter()
+    .map(|(at, seed)| {
+      let ids = &sites[&site(seed)];
+      let index = ids.iter().position(|id| *id == seed.id).unwrap_or_default();
+      Reverse((at + jitter(seed, index, ids.len()), seed.id))
+    })
+    .collect()
+}
+
+/// 每隔 [`PROBE_INTERVAL`] 探测一次网络，网络恢复或调度器被唤醒时返回
+async fn wait_online(app_handle: &AppHandle, seed_ids: &[i64]) {
+  loop {
+    if timeout(PROBE_INTERVAL, wake().notified()).await.is_ok() {
+      return;
+    }
+
+    match probe_network(app_handle, seed_ids).await {
+      Ok(true) => {
+        info!("The network is available again");
+        return;
+      }
+      Ok(false) => {}
+      Err(err) => warn!("Failed to probe the network: {:?}", err),
+    }
+  }
+}
+
+/// 运行调度器，在种子到期时抓取
+pub async fn run(app_handle: AppHandle) {
+  // 网络不可用时的等待时间
+  let mut offline_backoff = None;
+
+  loop {
+    let mut queue = match app_handle.db(get_all_seeds).await {
+      Ok(seeds) => build_queue(&seeds),
+      Err(err) => {
+        warn!("Failed to load seeds: {:?}", err);
+        BinaryHeap::new()
+      }
+    };
+
+    let now = Local::now().timestamp();
+    let mut due = Vec::new();
+
+    while let Some(Reverse((at, seed_id))) = queue.peek().copied() {
+      if at > now {
+        break;
+      }
+
+      queue.pop();
+      due.push(seed_id);
+    }
+
+    if due.is_empty() {
+      let wait = queue
+        .peek()
+        .map(|Reverse((at, _))| Duration::from_secs((at - now) as u64))
+        .map_or(MAX_WAIT, |wait| wait.min(MAX_WAIT));
+      debug!("Next fetch in {:?}", wait);
+
+      let _ = timeout(wait, wake().notified()).await;
+      continue;
+    }
+
+    match fetch_scheduled(&app_handle, &due).await {
+      Ok(ScheduledFetch::Done) => {
+        offline_backoff = None;
+      }
+      Ok(ScheduledFetch::Busy) => {
+        debug!("A refresh is running, retry later");
+        let _ = timeout(RETRY_DELAY, wake().notified()).await;
+      }
+      Ok(ScheduledFetch::Offline) => {
+        // 网络不可用时暂停，等待时间逐渐加倍，到期后重新尝试。
+        // 等待期间定期探测网络，网络恢复后立即恢复正常抓取
+        let backoff = offline_backoff.map_or(MIN_OFFLINE_BACKOFF, |backoff: Duration| {
+          (backoff * 2).min(MAX_OFFLINE_BACKOFF)
+        });
+        offline_backoff = Some(backoff);
+        info!("Offline, retry in {:?}", backoff);
+        let _ = timeout(backoff, wait_online(&app_handle, &due)).await;
+      }
+      Err(err) => {
+        error!("Failed to fetch seeds: {:?}", err);
+        let _ = timeout(RETRY_DELAY, wake().notified()).await;
+      }
+    }
+  }
+}
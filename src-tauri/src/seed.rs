@@ -1,4 +1,3 @@
-use chrono::Local;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -11,11 +10,7 @@ pub struct Seed {
   pub name: String,
   /** URL */
   pub url: String,
-  /**
-   * 图标
-   *
-   * TODO: 保存 URL 或 base64，估计是后者
-   */
+  /** 图标，data URL */
   pub favicon: Option<String>,
   /** 更新周期，分钟 */
   pub interval: i32,
@@ -23,27 +18,70 @@ pub struct Seed {
   pub last_fetched_at: i64,
   /** 最近抓取是否成功 */
   pub last_fetch_ok: bool,
+  /** 最近抓取失败的原因 */
+  pub last_fetch_error: Option<String>,
+  /** 所在文件夹 ID */
+  pub folder_id: Option<i64>,
+  /** 是否通知新文章，`null` 表示跟随全局设置 */
+  pub notify: Option<bool>,
+  /** 是否暂停定时抓取 */
+  pub paused: bool,
+  /** 最近一次抓取到的频道信息 */
+  pub channel: ChannelInfo,
+  /** 是否自动提取新文章的正文 */
+  pub auto_extract: bool,
+  /** 提取正文的 CSS 选择器，`null` 表示自动查找正文 */
+  pub extract_selector: Option<String>,
 }
 
-impl Seed {
-  /// 是否应该抓取
-  pub fn should_fetch(&self) -> bool {
-    if self.last_fetched_at > 0 {
-      // 上次抓取过了，计算下次抓取时间
-      let next_fetch = self.last_fetched_at + i64::from(self.interval * 60);
-      let now = Local::now().timestamp();
+/// 订阅源的频道信息
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+pub struct ChannelInfo {
+  /// 标题
+  pub title: Option<String>,
+  /// 描述
+  pub description: Option<String>,
+  /// 网站链接
+  pub link: Option<String>,
+  /// 语言
+  pub language: Option<String>,
+  /// 图片 URL
+  pub image: Option<String>,
+  /// 生成器
+  pub generator: Option<String>,
+  /// 最近更新时间，UNIX Epoch
+  pub last_build_date: Option<i64>,
+}
 
-      // 如果下次抓取时间还没到，则不该抓取
-      if next_fetch > now {
-        return false;
-      }
+impl Seed {
+  /// 下次定时抓取的时间，UNIX Epoch，暂停时为 `None`
+  pub fn next_fetch_at(&self) -> Option<i64> {
+    if self.paused {
+      return None;
     }
 
-    // 可
-    true
+    if self.last_fetched_at > 0 {
+      // 上次抓取过了，按更新周期计算下次抓取时间，周期至少 1 分钟
+      Some(self.last_fetched_at + i64::from(self.interval.max(1)) * 60)
+    } else {
+      // 从未抓取过，立即抓取
+      Some(0)
+    }
   }
 }
 
+/// 文件夹
+#[derive(Debug, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+  /// ID
+  pub id: i64,
+  /// 名称
+  pub name: String,
+  /// 上级文件夹 ID
+  pub parent_id: Option<i64>,
+}
+
 /// 文章
 #[derive(Debug, Deserialize, Serialize, Type)]
 pub struct Article {
@@ -59,10 +97,58 @@ pub struct Article {
   pub author: Option<String>,
   /// 描述
   pub desc: Option<String>,
+  /// 全文内容
+  pub content: Option<String>,
   /// 链接
   pub link: String,
   /// 发布时间，UNIX Epoch
   pub pub_date: i64,
   /// 是否未读
   pub unread: bool,
+  /// 是否加了星标
+  pub starred: bool,
+  /// 标签
+  pub tags: Vec<String>,
+  /// 搜索结果的高亮摘要
+  pub snippet: Option<String>,
+}
+
+/// 标签
+#[derive(Debug, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+  /// ID
+  pub id: i64,
+  /// 名称
+  pub name: String,
+  /// 文章数量
+  pub article_count: i32,
+}
+
+/// 种子的抓取状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum FetchStatus {
+  /// 成功
+  Ok,
+  /// 服务器返回 304，没有变化
+  NotModified,
+  /// 失败
+  Error,
+  /// 网络不可用，跳过了抓取，没有记录结果
+  Offline,
+}
+
+/// 种子的抓取结果，也是单个种子抓取完成事件的内容
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchResult {
+  /// 种子 ID
+  pub seed_id: i64,
+  /// 抓取状态
+  pub status: FetchStatus,
+  /// 失败原因
+  pub error: Option<String>,
+  /// 抓取时间，UNIX Epoch
+  pub fetched_at: i64,
 }
@@ -0,0 +1,176 @@
+use regex::{escape, Regex};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// 匹配方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchMode {
+  /// 包含
+  #[default]
+  Substring,
+  /// 整词
+  WholeWord,
+  /// 正则表达式
+  Regex,
+}
+
+impl MatchMode {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Substring => "substring",
+      Self::WholeWord => "wholeWord",
+      Self::Regex => "regex",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "wholeWord" => Self::WholeWord,
+      "regex" => Self::Regex,
+      _ => Self::Substring,
+    }
+  }
+}
+
+/// 匹配的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchField {
+  /// 标题
+  Title,
+  /// 作者
+  Author,
+  /// 描述
+  Description,
+  /// 全文内容
+  Content,
+}
+
+impl MatchField {
+  /// 对应列的 `REGEXP` 条件，列名带表名前缀，与全文搜索表连接时不会有歧义
+  pub fn regexp_condition(&self) -> &'static str {
+    match self {
+      Self::Title => "articles.title REGEXP ?",
+      Self::Author => "articles.author REGEXP ?",
+      Self::Description => "articles.desc REGEXP ?",
+      Self::Content => "articles.content REGEXP ?",
+    }
+  }
+
+  /// 待匹配文章中对应字段的值
+  pub fn value<'a>(&self, candidate: &Candidate<'a>) -> Option<&'a str> {
+    match self {
+      Self::Title => candidate.title,
+      Self::Author => candidate.author,
+      Self::Description => candidate.description,
+      Self::Content => candidate.content,
+    }
+  }
+}
+
+/// 待匹配的文章，用于在插入数据库之前进行匹配
+#[derive(Debug, Default)]
+pub struct Candidate<'a> {
+  /// 种子 ID
+  pub seed_id: i64,
+  /// 种子所在的文件夹及其所有上级文件夹
+  pub folder_ids: &'a [i64],
+  /// 标题
+  pub title: Option<&'a str>,
+  /// 作者
+  pub author: Option<&'a str>,
+  /// 描述
+  pub description: Option<&'a str>,
+  /// 全文内容
+  pub content: Option<&'a str>,
+}
+
+/// 默认只匹配标题
+fn default_fields() -> Vec<MatchField> {
+  vec![MatchField::Title]
+}
+
+fn default_case_sensitive() -> bool {
+  true
+}
+
+/// 文章匹配条件
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Matcher {
+  /// 关键字或正则表达式
+  pub keyword: String,
+  /// 匹配方式
+  #[serde(default)]
+  pub match_mode: MatchMode,
+  /// 是否区分大小写
+  #[serde(default = "default_case_sensitive")]
+  pub case_sensitive: bool,
+  /// 匹配的字段
+  #[serde(default = "default_fields")]
+  pub fields: Vec<MatchField>,
+  /// 只匹配此种子
+  pub seed_id: Option<i64>,
+  /// 只匹配此文件夹（含子文件夹）
+  pub folder_id: Option<i64>,
+}
+
+impl Matcher {
+  /// 转换为正则表达式，SQL 的 `REGEXP` 与 Rust 中的匹配使用同一个表达式
+  pub fn pattern(&self) -> String {
+    let pattern = match self.match_mode {
+      MatchMode::Substring => escape(&self.keyword),
+      MatchMode::WholeWord => format!(r"\b{}\b", escape(&self.keyword)),
+      MatchMode::Regex => self.keyword.clone(),
+    };
+
+    if self.case_sensitive {
+      pattern
+    } else {
+      format!("(?i){}", pattern)
+    }
+  }
+
+  /// 编译正则表达式，同时用于校验条件
+  pub fn to_regex(&self) -> Result<Regex, regex::Error> {
+    Regex::new(&self.pattern())
+  }
+
+  /// 是否有效
+  pub fn is_valid(&self) -> bool {
+    !self.keyword.is_empty() && !self.fields.is_empty() && self.to_regex().is_ok()
+  }
+
+  /// 匹配文章，`regex` 为 [`Matcher::to_regex`] 编译的正则表达式
+  pub fn is_match(&self, regex: &Regex, candidate: &Candidate) -> bool {
+    if self.seed_id.is_some_and(|id| id != candidate.seed_id) {
+      return false;
+    }
+
+    if self
+      .folder_id
+      .is_some_and(|id| !candidate.folder_ids.contains(&id))
+    {
+      return false;
+    }
+
+    self
+      .fields
+      .iter()
+      .filter_map(|field| field.value(candidate))
+      .any(|value| regex.is_match(value))
+  }
+}
+
+/// 监视规则
+#[derive(Debug, Clone, Deserialize, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchRule {
+  /// ID，新建时忽略
+  #[serde(default)]
+  pub id: i64,
+  /// 匹配条件
+  #[serde(flatten)]
+  pub matcher: Matcher,
+}
@@ -1,9 +1,11 @@
 import { appWindow } from '@tauri-apps/api/window';
-import { EyeIcon, PlusIcon, SearchIcon, SettingsIcon } from 'lucide-react';
+import { BellIcon, EyeIcon, PlusIcon, RefreshCwIcon, SearchIcon, SettingsIcon } from 'lucide-react';
 import { useEffect, useState } from 'react';
+import { toast } from 'sonner';
 import { useDebounceValue, useLocalStorage } from 'usehooks-ts';
 
 import AddSeedDialog from '@/components/AddSeedDialog';
+import ArticleDialog from '@/components/ArticleDialog';
 import ArticleList from '@/components/ArticleList';
 import SeedToggleItem from '@/components/SeedToggleItem';
 import SettingsDialog from '@/components/SettingsDialog';
@@ -13,8 +15,16 @@ import { ScrollArea } from '@/components/ui/scroll-area';
 import { Toggle } from '@/components/ui/toggle';
 import { ToggleGroup, ToggleGroupItem } from '@/components/ui/toggle-group';
 import WatchListDialog from '@/components/WatchListDialog';
-import { dbReadAll } from '@/lib/bindings';
+import {
+  type Article,
+  dbGetArticle,
+  dbReadAll,
+  getNotifiedArticle,
+  refresh,
+} from '@/lib/bindings';
+import useFetchStatus from '@/lib/useFetchStatus';
 import useSeeds from '@/lib/useSeeds';
+import { errorMessage } from '@/lib/utils';
 
 import '@/globals.css';
 import { Input } from './components/ui/input';
@@ -24,11 +34,42 @@ function App() {
   const [search, setSearch] = useDebounceValue('', 500);
   const [autoRead, setAutoRead] = useLocalStorage('autoRead', true);
   const { seeds } = useSeeds();
+  const { isFetching } = useFetchStatus();
 
   useEffect(() => {
     appWindow.show();
   }, []);
 
+  const [notified, setNotified] = useState<Article | null>(null);
+
+  // 在应用内显示最近通知的文章
+  const showNotified = async () => {
+    try {
+      const id = await getNotifiedArticle();
+
+      if (id === null) {
+        toast.info('No articles have been notified.');
+      } else {
+        setNotified(await dbGetArticle(id));
+      }
+    } catch (e) {
+      toast.error(errorMessage(e));
+    }
+  };
+
+  const refreshSeeds = async () => {
+    try {
+      const results = await refresh(seedId > 0 ? seedId : null, null);
+      const failed = results.filter((result) => result.status === 'error').length;
+
+      if (failed > 0) {
+        toast.error(`Failed to refresh ${failed} of ${results.length} seeds`);
+      }
+    } catch (e) {
+      toast.error(errorMessage(e));
+    }
+  };
+
   return (
     <ResizablePanelGroup direction="horizontal" autoSaveId="root">
       <ResizablePanel defaultSize={20} minSize={10}>
@@ -83,6 +124,13 @@ function App() {
           <Button disabled={seedId < 0} onClick={() => dbReadAll(seedId)}>
             Read all
           </Button>
+          <Button disabled={seedId < 0 || isFetching} onClick={refreshSeeds}>
+            <RefreshCwIcon className={isFetching ? 'animate-spin' : undefined} />
+            Refresh
+          </Button>
+          <Button variant="ghost" size="icon" title="Latest notification" onClick={showNotified}>
+            <BellIcon />
+          </Button>
           <div className="relative flex-1">
             <SearchIcon className="absolute left-2.5 top-2.5 h-4 w-4 text-muted-foreground" />
             <Input
@@ -96,6 +144,7 @@ function App() {
         <ScrollArea className="w-full @container">
           <ArticleList seedId={seedId === 0 ? null : seedId} search={search} />
         </ScrollArea>
+        <ArticleDialog article={notified} onClose={() => setNotified(null)} />
       </ResizablePanel>
     </ResizablePanelGroup>
   );
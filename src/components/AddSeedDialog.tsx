@@ -22,7 +22,8 @@ import {
   FormMessage,
 } from '@/components/ui/form';
 import { Input } from '@/components/ui/input';
-import { dbInsertSeed } from '@/lib/bindings';
+import { dbInsertSeed, fetchChannelInfo } from '@/lib/bindings';
+import { errorMessage } from '@/lib/utils';
 
 export const addSeedSchema = z.object({
   name: z.string().min(1),
@@ -45,6 +46,23 @@ export default function AddSeedDialog(props: AddSeedDialogProps) {
     },
   });
 
+  // 没有填写名称时，使用频道标题
+  const fillName = async (url: string) => {
+    if (form.getValues('name') || !addSeedSchema.shape.url.safeParse(url).success) {
+      return;
+    }
+
+    try {
+      const info = await fetchChannelInfo(url);
+
+      if (info.title && !form.getValues('name')) {
+        form.setValue('name', info.title, { shouldValidate: true });
+      }
+    } catch (e) {
+      console.warn('Failed to fetch channel info', e);
+    }
+  };
+
   return (
     <Dialog open={open} onOpenChange={setOpen}>
       <DialogTrigger asChild>{children}</DialogTrigger>
@@ -57,12 +75,11 @@ export default function AddSeedDialog(props: AddSeedDialogProps) {
             className="flex flex-col gap-2"
             onSubmit={form.handleSubmit(async (values) => {
               const { name, url } = values;
-              const result = await dbInsertSeed(name, url);
-
-              if (result) {
+              try {
+                await dbInsertSeed(name, url);
                 setOpen(false);
-              } else {
-                toast.error('Failed to add seed.');
+              } catch (e) {
+                toast.error(`Failed to add seed: ${errorMessage(e)}`);
               }
             })}
           >
@@ -86,7 +103,15 @@ export default function AddSeedDialog(props: AddSeedDialogProps) {
                 <FormItem>
                   <FormLabel>URL</FormLabel>
                   <FormControl>
-                    <Input required type="url" {...field} />
+                    <Input
+                      required
+                      type="url"
+                      {...field}
+                      onBlur={() => {
+                        field.onBlur();
+                        fillName(field.value);
+                      }}
+                    />
                   </FormControl>
                   <FormMessage />
                 </FormItem>
@@ -0,0 +1,60 @@
+import { open } from '@tauri-apps/api/shell';
+import { ExternalLinkIcon } from 'lucide-react';
+import { useEffect } from 'react';
+
+import { Button } from '@/components/ui/button';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { ScrollArea } from '@/components/ui/scroll-area';
+import { type Article, dbReadArticle } from '@/lib/bindings';
+
+type ArticleDialogProps = {
+  article: Article | null;
+  onClose: () => void;
+};
+
+// 在应用内显示文章，内容在保存时已经清理过
+export default function ArticleDialog(props: ArticleDialogProps) {
+  const { article, onClose } = props;
+
+  useEffect(() => {
+    if (article?.unread) {
+      dbReadArticle(article.id, true);
+    }
+  }, [article]);
+
+  if (!article) {
+    return null;
+  }
+
+  const time = new Date(article.pub_date * 1000);
+
+  return (
+    <Dialog open onOpenChange={(value) => !value && onClose()}>
+      <DialogContent className="max-w-3xl">
+        <DialogHeader>
+          <DialogTitle>{article.title}</DialogTitle>
+          <DialogDescription>
+            {article.seed_name} ·{' '}
+            <time dateTime={time.toISOString()}>{time.toLocaleString()}</time>
+          </DialogDescription>
+        </DialogHeader>
+        <ScrollArea className="max-h-[60vh]">
+          <div dangerouslySetInnerHTML={{ __html: article.content ?? article.desc ?? '' }} />
+        </ScrollArea>
+        <DialogFooter>
+          <Button onClick={() => open(article.link)}>
+            <ExternalLinkIcon />
+            Open link
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
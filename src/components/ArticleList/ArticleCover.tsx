@@ -1,7 +1,9 @@
-import { useRef } from 'react';
-import { useEventListener } from 'usehooks-ts';
+import type { Event } from '@tauri-apps/api/event';
+import { useCallback, useMemo, useState } from 'react';
 
-import { download } from '../../lib/bindings';
+import type { MediaCachedEvent } from '../../lib/bindings';
+import useEvent from '../../lib/useEvent';
+import { mediaUrl } from '../../lib/utils';
 
 type ItemCoverProps = {
   desc: string | null;
@@ -10,21 +12,8 @@ type ItemCoverProps = {
 
 const blackList = ['https://m.av28.tv'];
 
-const findProp = (desc: string, imgStart: number, imgEnd: number, prop: string) => {
-  const search = `${prop}="`;
-  const start = desc.indexOf(search, imgStart);
-
-  if (start < 0 || start >= imgEnd) {
-    return null;
-  }
-
-  const end = desc.indexOf('"', start + search.length);
-  const value = desc.substring(start + search.length, end);
-  return value;
-};
-
-const findDataLink = (desc: string, imgStart: number, imgEnd: number) => {
-  const dataLink = findProp(desc, imgStart, imgEnd, 'data-link');
+const findDataLink = (img: HTMLImageElement) => {
+  const dataLink = img.getAttribute('data-link');
 
   if (!dataLink) {
     return null;
@@ -37,53 +26,60 @@ const findDataLink = (desc: string, imgStart: number, imgEnd: number) => {
   return 'maybe';
 };
 
+// 用 DOMParser 解析，属性值中的字符引用由浏览器解码；解析出的文档是惰性的，不会加载图片或执行脚本
+const findCover = (desc: string) => {
+  const doc = new DOMParser().parseFromString(desc, 'text/html');
+
+  for (const img of doc.querySelectorAll('img')) {
+    const src = img.getAttribute('src');
+
+    if (src && !findDataLink(img)) {
+      return src;
+    }
+  }
+
+  return null;
+};
+
+// 与后端解析后的 URL 比较
+const normalize = (url: string) => {
+  try {
+    return new URL(url).href;
+  } catch {
+    return url;
+  }
+};
+
 export default function ItemCover(props: ItemCoverProps) {
   const { desc, link } = props;
-  let imgRef = useRef<HTMLImageElement>(null);
-
-  useEventListener(
-    'error',
-    async () => {
-      if (imgRef.current) {
-        imgRef.current.src = await download(imgRef.current.src, link);
+  const src = useMemo(() => (desc ? findCover(desc) : null), [desc]);
+  const [version, setVersion] = useState(0);
+
+  // 图片在后台缓存完成后重新加载
+  const cachedHandler = useCallback(
+    (event: Event<MediaCachedEvent>) => {
+      if (src && event.payload.url === normalize(src)) {
+        setVersion((v) => v + 1);
       }
     },
-    imgRef,
-    { once: true, passive: true },
+    [src],
   );
 
+  useEvent('app://media/cached', cachedHandler);
+
   if (!desc) {
     return null;
   }
 
-  let imgStart = 0;
-
-  while (imgStart >= 0) {
-    imgStart = desc.indexOf('<img ', imgStart);
-
-    if (imgStart >= 0) {
-      const imgEnd = desc.indexOf('>', imgStart);
-
-      const src = findProp(desc, imgStart, imgEnd, 'src');
-
-      if (src) {
-        const dataLink = findDataLink(desc, imgStart, imgEnd);
-
-        if (!dataLink) {
-          return (
-            <img
-              ref={imgRef}
-              src={src}
-              decoding="async"
-              loading="lazy"
-              referrerPolicy="no-referrer"
-            />
-          );
-        }
-      }
-
-      imgStart = imgEnd;
-    }
+  if (src) {
+    return (
+      <img
+        src={version ? `${mediaUrl(src, link)}&v=${version}` : mediaUrl(src, link)}
+        decoding="async"
+        loading="lazy"
+        referrerPolicy="no-referrer"
+      />
+    );
   }
 
   return desc;
@@ -28,6 +28,7 @@ import {
 } from '@/components/ui/form';
 import { Input } from '@/components/ui/input';
 import { dbUpdateSeed, type Seed } from '@/lib/bindings';
+import { errorMessage } from '@/lib/utils';
 import { addSeedSchema, type AddSeedType } from './AddSeedDialog';
 
 type EditSeedDialogProps = {
@@ -65,12 +66,11 @@ export default function EditSeedDialog(props: EditSeedDialogProps) {
             className="flex flex-col gap-2"
             onSubmit={form.handleSubmit(async (values) => {
               const { name, url } = values;
-              const result = await dbUpdateSeed(seed.id, name, url);
-
-              if (result) {
+              try {
+                await dbUpdateSeed(seed.id, name, url);
                 setOpen(false);
-              } else {
-                toast.error('Failed to save seed.');
+              } catch (e) {
+                toast.error(`Failed to save seed: ${errorMessage(e)}`);
               }
             })}
           >
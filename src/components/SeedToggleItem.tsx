@@ -6,7 +6,7 @@ import { forwardRef } from 'react';
 import EditSeedDialog from './EditSeedDialog';
 
 type SeedToggleItemProps = {
-  seed: Pick<Seed, 'id' | 'name' | 'url'> | null;
+  seed: Pick<Seed, 'id' | 'name' | 'url' | 'favicon'> | null;
 };
 
 const SeedToggleItem = forwardRef<HTMLButtonElement, SeedToggleItemProps>(
@@ -18,7 +18,11 @@ const SeedToggleItem = forwardRef<HTMLButtonElement, SeedToggleItemProps>(
       return (
         <EditSeedDialog seed={seed}>
           <ToggleGroupItem ref={ref} className="w-full justify-start" value={seed.id.toString()}>
-            <RssIcon />
+            {seed.favicon ? (
+              <img className="h-4 w-4" src={seed.favicon} alt="" />
+            ) : (
+              <RssIcon />
+            )}
             <span>{seed.name}</span>
             <span className="ms-auto font-mono">{unread || ''}</span>
           </ToggleGroupItem>
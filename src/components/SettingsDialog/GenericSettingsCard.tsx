@@ -27,23 +27,27 @@ import useSetting from '@/lib/useSettings';
 
 const genericSchema = z.object({
   timeout: z.coerce.number().int().min(0),
+  cacheSize: z.coerce.number().int().min(0),
+  allowPrivateNetwork: z.boolean(),
 });
 
 export type GenericSettings = z.infer<typeof genericSchema>;
 
 const defaultGenericSettings = Object.freeze<GenericSettings>({
   timeout: 30,
+  cacheSize: 200,
+  allowPrivateNetwork: false,
 });
 
 export default function GenericSettingsCard() {
   const [generic, save] = useSetting('generic', defaultGenericSettings);
   const form = useForm<GenericSettings>({
     resolver: zodResolver(genericSchema),
-    defaultValues: generic,
+    defaultValues: { ...defaultGenericSettings, ...generic },
   });
 
   useEffect(() => {
-    form.reset(generic);
+    form.reset({ ...defaultGenericSettings, ...generic });
   }, [generic]);
 
   return (
@@ -79,6 +83,35 @@ export default function GenericSettingsCard() {
                 </FormItem>
               )}
             />
+            <FormField
+              control={form.control}
+              name="cacheSize"
+              render={({ field }) => (
+                <FormItem>
+                  <FormLabel>Image cache size (in MB)</FormLabel>
+                  <FormControl>
+                    <Input {...field} required type="number" />
+                  </FormControl>
+                  <FormMessage />
+                </FormItem>
+              )}
+            />
+            <FormField
+              control={form.control}
+              name="allowPrivateNetwork"
+              render={({ field }) => (
+                <FormItem className="flex flex-row items-center gap-2 space-y-0">
+                  <FormControl>
+                    <input
+                      type="checkbox"
+                      checked={field.value}
+                      onChange={(e) => field.onChange(e.target.checked)}
+                    />
+                  </FormControl>
+                  <FormLabel>Allow loading images from local and private network addresses</FormLabel>
+                </FormItem>
+              )}
+            />
           </CardContent>
           <CardFooter className="flex flex-row-reverse">
             <Button type="submit">
@@ -1,5 +1,6 @@
 import { MinusIcon, PlusIcon } from 'lucide-react';
 import { Fragment, useRef, type ReactNode } from 'react';
+import { toast } from 'sonner';
 
 import { Button } from '@/components/ui/button';
 import {
@@ -12,6 +13,7 @@ import {
 } from '@/components/ui/dialog';
 import { Input } from '@/components/ui/input';
 import useWatchList from '@/lib/useWatchList';
+import { errorMessage } from '@/lib/utils';
 import { dbAddWatchKeyword, dbDeleteWatchKeyword } from '../lib/bindings';
 import { ScrollArea } from './ui/scroll-area';
 
@@ -26,11 +28,12 @@ export default function WatchListDialog(props: WatchListDialogProps) {
 
   const add = async () => {
     if (keywordRef.current?.value) {
-      const ok = await dbAddWatchKeyword(keywordRef.current.value);
-
-      if (ok) {
+      try {
+        await dbAddWatchKeyword(keywordRef.current.value);
         keywordRef.current.value = '';
         keywordRef.current.focus();
+      } catch (e) {
+        toast.error(errorMessage(e));
       }
     }
   };
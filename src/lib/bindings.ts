@@ -14,14 +14,70 @@ const invoke = () => window.__TAURI_INVOKE__;
  * 添加监视关键字。
  */
 export function dbAddWatchKeyword(keyword: string) {
-    return invoke()<boolean>("db_add_watch_keyword", { keyword })
+    return invoke()<null>("db_add_watch_keyword", { keyword })
+}
+
+/**
+ * 对已有文章应用规则，禁用的规则不能应用。
+ */
+export function dbApplyRule(ruleId: number) {
+    return invoke()<null>("db_apply_rule", { ruleId })
+}
+
+/**
+ * 删除文件夹，其中的种子和子文件夹移动到上级文件夹。
+ */
+export function dbDeleteFolder(folderId: number) {
+    return invoke()<null>("db_delete_folder", { folderId })
+}
+
+/**
+ * 删除静音规则，被其隐藏的文章重新显示。
+ */
+export function dbDeleteMuteRule(ruleId: number) {
+    return invoke()<null>("db_delete_mute_rule", { ruleId })
+}
+
+/**
+ * 删除规则。
+ */
+export function dbDeleteRule(ruleId: number) {
+    return invoke()<null>("db_delete_rule", { ruleId })
+}
+
+/**
+ * 删除保存的搜索。
+ */
+export function dbDeleteSavedSearch(searchId: number) {
+    return invoke()<null>("db_delete_saved_search", { searchId })
+}
+
+/**
+ * 删除标签。
+ */
+export function dbDeleteTag(tag: string) {
+    return invoke()<null>("db_delete_tag", { tag })
 }
 
 /**
  * 删除监视关键字。
  */
 export function dbDeleteWatchKeyword(keyword: string) {
-    return invoke()<boolean>("db_delete_watch_keyword", { keyword })
+    return invoke()<null>("db_delete_watch_keyword", { keyword })
+}
+
+/**
+ * 删除监视规则。
+ */
+export function dbDeleteWatchRule(ruleId: number) {
+    return invoke()<null>("db_delete_watch_rule", { ruleId })
+}
+
+/**
+ * 获取所有文件夹。
+ */
+export function dbGetAllFolders() {
+    return invoke()<Folder[]>("db_get_all_folders")
 }
 
 /**
@@ -31,6 +87,13 @@ export function dbGetAllSeeds() {
     return invoke()<Seed[]>("db_get_all_seeds")
 }
 
+/**
+ * 获取单篇文章。
+ */
+export function dbGetArticle(articleId: number) {
+    return invoke()<Article>("db_get_article", { articleId })
+}
+
 /**
  * 获取文章。
  */
@@ -38,6 +101,34 @@ export function dbGetArticles(filters: ArticleFilters) {
     return invoke()<ArticleResult>("db_get_articles", { filters })
 }
 
+/**
+ * 获取文件夹未读数量。
+ */
+export function dbGetFolderUnreadCount(folderId: number) {
+    return invoke()<number>("db_get_folder_unread_count", { folderId })
+}
+
+/**
+ * 获取静音规则列表。
+ */
+export function dbGetMuteRules() {
+    return invoke()<MuteRule[]>("db_get_mute_rules")
+}
+
+/**
+ * 获取规则列表。
+ */
+export function dbGetRules() {
+    return invoke()<Rule[]>("db_get_rules")
+}
+
+/**
+ * 获取所有保存的搜索。
+ */
+export function dbGetSavedSearches() {
+    return invoke()<SavedSearch[]>("db_get_saved_searches")
+}
+
 /**
  * 获取设置。
  */
@@ -45,6 +136,13 @@ export function dbGetSetting(key: string) {
     return invoke()<string>("db_get_setting", { key })
 }
 
+/**
+ * 获取所有标签及其文章数量。
+ */
+export function dbGetTags() {
+    return invoke()<Tag[]>("db_get_tags")
+}
+
 /**
  * 获取未读数量。
  */
@@ -59,55 +157,299 @@ export function dbGetWatchList() {
     return invoke()<string[]>("db_get_watch_list")
 }
 
+/**
+ * 获取监视规则列表。
+ */
+export function dbGetWatchRules() {
+    return invoke()<WatchRule[]>("db_get_watch_rules")
+}
+
+/**
+ * 创建文件夹。
+ */
+export function dbInsertFolder(name: string, parentId: number | null) {
+    return invoke()<null>("db_insert_folder", { name,parentId })
+}
+
+/**
+ * 保存搜索。
+ */
+export function dbInsertSavedSearch(name: string, query: SavedQuery) {
+    return invoke()<null>("db_insert_saved_search", { name,query })
+}
+
 /**
  * 插入种子。
  */
 export function dbInsertSeed(name: string, url: string) {
-    return invoke()<boolean>("db_insert_seed", { name,url })
+    return invoke()<null>("db_insert_seed", { name,url })
+}
+
+/**
+ * 移动文件夹到另一个文件夹下。
+ */
+export function dbMoveFolder(folderId: number, parentId: number | null) {
+    return invoke()<null>("db_move_folder", { folderId,parentId })
+}
+
+/**
+ * 移动种子到文件夹。
+ */
+export function dbMoveSeed(seedId: number, folderId: number | null) {
+    return invoke()<null>("db_move_seed", { seedId,folderId })
 }
 
 /**
  * 将文章标记为已读或未读。
  */
 export function dbReadArticle(itemId: number, read: boolean) {
-    return invoke()<boolean>("db_read_article", { itemId,read })
+    return invoke()<null>("db_read_article", { itemId,read })
 }
 
 /**
  * 全部标记为已读
  */
 export function dbReadAll(seedId: number | null) {
-    return invoke()<boolean>("db_read_all", { seedId })
+    return invoke()<null>("db_read_all", { seedId })
+}
+
+/**
+ * 将文件夹（含子文件夹）中的文章全部标记为已读
+ */
+export function dbReadFolder(folderId: number) {
+    return invoke()<null>("db_read_folder", { folderId })
+}
+
+/**
+ * 重命名文件夹。
+ */
+export function dbRenameFolder(folderId: number, name: string) {
+    return invoke()<null>("db_rename_folder", { folderId,name })
+}
+
+/**
+ * 添加或修改静音规则，`id` 为 0 时添加。
+ */
+export function dbSaveMuteRule(rule: MuteRule) {
+    return invoke()<null>("db_save_mute_rule", { rule })
+}
+
+/**
+ * 添加或修改规则，`id` 为 0 时添加。
+ */
+export function dbSaveRule(rule: Rule) {
+    return invoke()<null>("db_save_rule", { rule })
+}
+
+/**
+ * 添加或修改监视规则，`id` 为 0 时添加。
+ */
+export function dbSaveWatchRule(rule: WatchRule) {
+    return invoke()<null>("db_save_watch_rule", { rule })
+}
+
+/**
+ * 设置种子的正文提取选项，`selector` 为空表示自动查找正文。
+ */
+export function dbSetSeedExtract(seedId: number, autoExtract: boolean, selector: string | null) {
+    return invoke()<null>("db_set_seed_extract", { seedId,autoExtract,selector })
+}
+
+/**
+ * 设置种子的通知，`None` 表示跟随全局设置。
+ */
+export function dbSetSeedNotify(seedId: number, notify: boolean | null) {
+    return invoke()<null>("db_set_seed_notify", { seedId,notify })
+}
+
+/**
+ * 暂停或恢复种子的定时抓取。
+ */
+export function dbSetSeedPaused(seedId: number, paused: boolean) {
+    return invoke()<null>("db_set_seed_paused", { seedId,paused })
 }
 
 /**
  * 修改设置。
  */
 export function dbSetSetting(key: string, value: string) {
-    return invoke()<boolean>("db_set_setting", { key,value })
+    return invoke()<null>("db_set_setting", { key,value })
+}
+
+/**
+ * 为文章加星标或取消星标。
+ */
+export function dbStarArticle(itemId: number, starred: boolean) {
+    return invoke()<null>("db_star_article", { itemId,starred })
+}
+
+/**
+ * 为文章添加标签。
+ */
+export function dbTagArticle(itemId: number, tag: string) {
+    return invoke()<null>("db_tag_article", { itemId,tag })
+}
+
+/**
+ * 取消文章的静音，重新显示。
+ */
+export function dbUnmuteArticle(itemId: number) {
+    return invoke()<null>("db_unmute_article", { itemId })
+}
+
+/**
+ * 移除文章的标签。
+ */
+export function dbUntagArticle(itemId: number, tag: string) {
+    return invoke()<null>("db_untag_article", { itemId,tag })
+}
+
+/**
+ * 修改保存的搜索。
+ */
+export function dbUpdateSavedSearch(searchId: number, name: string, query: SavedQuery) {
+    return invoke()<null>("db_update_saved_search", { searchId,name,query })
 }
 
 /**
  * 更新种子。
  */
 export function dbUpdateSeed(seedId: number, name: string, url: string) {
-    return invoke()<boolean>("db_update_seed", { seedId,name,url })
+    return invoke()<null>("db_update_seed", { seedId,name,url })
+}
+
+/**
+ * 下载指定 URL 的图片，返回 data URL。优先使用图片缓存，下载后加入缓存。
+ * 
+ * 只允许 HTTP(S)，默认不允许本机和内网地址。`headers` 为额外的请求头。
+ */
+export function download(url: string, referer: string | null, headers: { [key: string]: string } | null) {
+    return invoke()<string>("download", { url,referer,headers })
+}
+
+/**
+ * 提取文章的正文，使用种子的 CSS 选择器或自动查找正文，保存为文章的内容并返回。
+ */
+export function extractArticle(articleId: number) {
+    return invoke()<string>("extract_article", { articleId })
+}
+
+/**
+ * 读取订阅源的频道信息，用于添加种子时自动填写名称
+ */
+export function fetchChannelInfo(url: string) {
+    return invoke()<ChannelInfo>("fetch_channel_info", { url })
 }
 
 /**
- * 下载指定 URL 的数据
+ * 获取最近一次通知的文章 ID，没有通知过时返回 `None`。
  */
-export function download(url: string, referer: string | null) {
-    return invoke()<string>("download", { url,referer })
+export function getNotifiedArticle() {
+    return invoke()<number | null>("get_notified_article")
+}
+
+/**
+ * 立即抓取种子，不考虑更新周期。指定 `seed_id` 时只抓取该种子，指定 `folder_id` 时抓取文件夹（含子文件夹）下的种子，都为空时抓取所有种子。
+ * 
+ * 指定文件夹或抓取所有种子时跳过已暂停的种子。
+ */
+export function refresh(seedId: number | null, folderId: number | null) {
+    return invoke()<FetchResult[]>("refresh", { seedId,folderId })
 }
 
 /**
  * 文章
  */
-export type Article = { id: number; seed_id: number; seed_name: string; title: string | null; author: string | null; desc: string | null; link: string; pub_date: number; unread: boolean }
-export type ArticleFilters = { seedId: number | null; cursor: string | null; limit: number | null; search: string | null }
+export type Article = { id: number; seed_id: number; seed_name: string; title: string | null; author: string | null; desc: string | null; content: string | null; link: string; pub_date: number; unread: boolean; starred: boolean; tags: string[]; snippet: string | null }
+export type ArticleFilters = { seedId: number | null; folderId: number | null; cursor: string | null; limit: number | null; search: string | null; tag: string | null; seedIds: number[] | null; readState: ReadState | null; starred: boolean | null; author: string | null; since: number | null; until: number | null; sort: ArticleSort | null; muted: boolean | null }
 /**
  * 种子
  */
-export type Seed = { id: number; name: string; url: string; favicon: string | null; interval: number; last_fetched_at: number; last_fetch_ok: boolean }
+export type Seed = { id: number; name: string; url: string; favicon: string | null; interval: number; last_fetched_at: number; last_fetch_ok: boolean; last_fetch_error: string | null; folder_id: number | null; notify: boolean | null; paused: boolean; channel: ChannelInfo; auto_extract: boolean; extract_selector: string | null }
+/**
+ * 订阅源的频道信息
+ */
+export type ChannelInfo = { title: string | null; description: string | null; link: string | null; language: string | null; image: string | null; generator: string | null; last_build_date: number | null }
+/**
+ * 种子的抓取结果，也是单个种子抓取完成事件的内容
+ */
+export type FetchResult = { seedId: number; status: FetchStatus; error: string | null; fetchedAt: number }
+/**
+ * 种子的抓取状态
+ */
+export type FetchStatus = "ok" | "notModified" | "error" | "offline"
+/**
+ * 文件夹
+ */
+export type Folder = { id: number; name: string; parentId: number | null }
+/**
+ * 标签
+ */
+export type Tag = { id: number; name: string; articleCount: number }
+/**
+ * 已读状态过滤
+ */
+export type ReadState = "unread" | "read" | "all"
+/**
+ * 文章排序方式
+ */
+export type ArticleSort = "newestFirst" | "oldestFirst" | "bySeed"
+/**
+ * 保存的搜索（智能文件夹）
+ */
+export type SavedSearch = { id: number; seedId: number; name: string; query: SavedQuery }
+/**
+ * 保存的搜索条件，各条件之间为“与”的关系
+ */
+export type SavedQuery = { seedIds: number[] | null; folderId: number | null; search: string | null; since: number | null; until: number | null; days: number | null; readState: ReadState | null; starred: boolean | null; tag: string | null; author: string | null }
+/**
+ * 监视规则
+ */
+export type WatchRule = ({ id: number }) & Matcher
+/**
+ * 文章匹配条件
+ */
+export type Matcher = { keyword: string; matchMode: MatchMode; caseSensitive: boolean; fields: MatchField[]; seedId: number | null; folderId: number | null }
+/**
+ * 静音规则
+ */
+export type MuteRule = ({ id: number; action: MuteAction; mutedCount: number }) & Matcher
+/**
+ * 静音方式
+ */
+export type MuteAction = "hide" | "markRead"
+/**
+ * 规则
+ */
+export type Rule = { id: number; name: string; enabled: boolean; condition: Matcher; actions: RuleAction[] }
+/**
+ * 规则的动作
+ */
+export type RuleAction = { type: "markRead" } | { type: "star" } | { type: "tag"; tag: string } | { type: "skip" } | { type: "notify" }
+/**
+ * 匹配方式
+ */
+export type MatchMode = "substring" | "wholeWord" | "regex"
+/**
+ * 匹配的字段
+ */
+export type MatchField = "title" | "author" | "description" | "content"
 export type ArticleResult = { articles: Article[]; nextCursor: string | null }
+/**
+ * 返回给前端的错误，序列化为 `{ code, message }`。
+ * 
+ * `code` 是稳定的错误代码，前端据此区分错误；`message` 可以直接显示给用户。
+ */
+export type Error = { code: "network"; message: string } | { code: "parse"; message: string } | { code: "database"; message: string } | { code: "notFound"; message: string } | { code: "validation"; message: string } | { code: "busy"; message: string } | { code: "internal"; message: string }
+/**
+ * 抓取开始事件
+ */
+export type FetchStartEvent = { seedIds: number[] }
+/**
+ * 抓取结束事件
+ */
+export type FetchFinishEvent = { ok: number; notModified: number; failed: number; offline: number }
+/**
+ * 图片缓存完成事件
+ */
+export type MediaCachedEvent = { url: string }
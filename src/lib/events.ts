@@ -3,6 +3,16 @@
  */
 export type ArticleReadEvent = { id: number; unread: boolean };
 
+/**
+ * 文章星标事件
+ */
+export type ArticleStarEvent = { id: number; starred: boolean };
+
+/**
+ * 文章标签事件
+ */
+export type ArticleTagEvent = { id: number; tags: string[] };
+
 /**
  * 种子未读数量事件
  */
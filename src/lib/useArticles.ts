@@ -11,7 +11,22 @@ const useItems = (seedId: Seed['id'] | null, search: string | null) => {
   const more = useRef<boolean>(true);
 
   const loadMore = useCallback(async () => {
-    const result = await dbGetArticles({ seedId, limit: null, cursor: cursor.current, search });
+    const result = await dbGetArticles({
+      seedId,
+      folderId: null,
+      limit: null,
+      cursor: cursor.current,
+      search,
+      tag: null,
+      seedIds: null,
+      readState: null,
+      starred: null,
+      author: null,
+      since: null,
+      until: null,
+      sort: null,
+      muted: null,
+    });
 
     if (cursor.current === null && more.current) {
       setArticles(result.articles);
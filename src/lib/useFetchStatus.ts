@@ -0,0 +1,31 @@
+import type { Event } from '@tauri-apps/api/event';
+import { useCallback, useState } from 'react';
+import type { FetchResult, FetchStartEvent } from './bindings';
+import useEvent from './useEvent';
+
+/**
+ * 正在抓取的种子
+ */
+const useFetchStatus = () => {
+  const [fetching, setFetching] = useState<number[]>([]);
+
+  const onStart = useCallback(({ payload }: Event<FetchStartEvent>) => {
+    setFetching(payload.seedIds);
+  }, []);
+
+  const onSeed = useCallback(({ payload }: Event<FetchResult>) => {
+    setFetching((ids) => ids.filter((id) => id !== payload.seedId));
+  }, []);
+
+  const onFinish = useCallback(() => {
+    setFetching([]);
+  }, []);
+
+  useEvent('app://fetch/start', onStart);
+  useEvent('app://fetch/seed', onSeed);
+  useEvent('app://fetch/finish', onFinish);
+
+  return { fetching, isFetching: fetching.length > 0 };
+};
+
+export default useFetchStatus;
@@ -14,6 +14,7 @@ const useSeeds = () => {
   }, []);
 
   useEvent('app://seed/add', refresh);
+  useEvent('app://fetch/finish', refresh);
 
   return { seeds, refresh };
 };
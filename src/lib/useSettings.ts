@@ -9,13 +9,13 @@ const useSettings = <T>(
 
   const save = useCallback(
     async (v: T) => {
-      const ok = await dbSetSetting(key, JSON.stringify(v));
-
-      if (ok) {
+      try {
+        await dbSetSetting(key, JSON.stringify(v));
         setValue(v);
+        return true;
+      } catch {
+        return false;
       }
-
-      return ok;
     },
     [setValue],
   );
@@ -1,6 +1,34 @@
+import { convertFileSrc } from '@tauri-apps/api/tauri';
 import { type ClassValue, clsx } from 'clsx';
 import { twMerge } from 'tailwind-merge';
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+/** 从命令返回的错误中取出可以显示给用户的消息 */
+export function errorMessage(e: unknown) {
+  if (typeof e === 'object' && e !== null && 'message' in e && typeof e.message === 'string') {
+    return e.message;
+  }
+
+  return String(e);
+}
+
+/** 媒体协议的地址，Windows 上为 `https://rssrs-media.localhost/` */
+const mediaBase = convertFileSrc('', 'rssrs-media');
+
+/** 通过媒体协议加载缓存的远程图片，没有缓存时在后台下载，完成后发送 `app://media/cached` 事件 */
+export function mediaUrl(url: string, referer?: string | null) {
+  if (!/^https?:/i.test(url)) {
+    return url;
+  }
+
+  const params = new URLSearchParams({ url });
+
+  if (referer) {
+    params.set('referer', referer);
+  }
+
+  return `${mediaBase}?${params}`;
+}